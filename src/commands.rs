@@ -1,26 +1,75 @@
 use crate::{
     errors::SerializeError,
-    traits::{Condition, Executable, Columns, Filtering, InsertElement},
+    functions,
+    schema::ColumnType,
+    traits::{
+        Condition, Executable, Filtering, Projection, Distinct, Join, JoinConstraint, JoinKind, JoinedTables,
+        TableReference, Aggregate, AggregateFunction, OrderBy, SortKey, Expression, ArithmeticOperator, ComparisonOperator,
+        Subquery, SetOperator, CommonTable, Window, WindowFunction, Frame, FrameBound,
+    },
+};
+use crate::utils::{Interval, Value as Val};
+use sqlparser::ast::{
+    Statement, SelectItem, SetExpr, Expr, TableFactor, Value, BinaryOperator, TableWithJoins, JoinOperator,
+    JoinConstraint as SqlJoinConstraint, Function, FunctionArg, FunctionArgExpr, GroupByExpr,
+    OrderByExpr, UnaryOperator, TrimWhereField, DateTimeField, DataType, Distinct as SqlDistinct, Query,
+    Select, SetOperator as SqlSetOperator, SetQuantifier, WindowType, WindowFrame, WindowFrameBound, WindowFrameUnits,
+    ObjectName, Ident,
 };
-use crate::utils::Value as Val;
-use sqlparser::ast::{Statement, SelectItem, SetExpr, Expr, TableFactor, Value, BinaryOperator};
-use std::{collections::HashMap, ops::Deref};
+use std::{collections::HashMap, ops::Deref, rc::Rc};
 
+use regex::Regex;
+
+// Commands are built once per statement, their size does not matter
+#[allow(clippy::large_enum_variant)]
 #[derive(Debug)]
 pub enum Command {
     Select {
-        tables: Vec<String>,
-        columns: Columns,
+        tables: Vec<JoinedTables>,
+        projections: Vec<Projection>,
+        distinct: Option<Distinct>,
         conditions: Result<Option<Condition>, SerializeError>,
+        /// Grouping keys, which may name or give the position of a selected column as well
+        group_by: Vec<Expression>,
+        aggregates: Vec<Aggregate>,
+        having: Result<Option<Condition>, SerializeError>,
+        windows: Vec<Window>,
+        order_by: Vec<OrderBy>,
+        limit: Option<usize>,
+        offset: usize,
+    },
+    /// Rows of two queries combined by UNION, INTERSECT or EXCEPT, duplicates being kept when `all` is set
+    SetOperation {
+        operator: SetOperator,
+        all: bool,
+        left: Box<Command>,
+        right: Box<Command>,
+        order_by: Vec<OrderBy>,
+        limit: Option<usize>,
+        offset: usize,
+    },
+    /// Query reading the common tables defined before it, which may read themselves when `recursive` is set
+    With {
+        recursive: bool,
+        tables: Vec<CommonTable>,
+        query: Box<Command>,
     },
     Update {
         table: String,
-        updates: HashMap<String, Val>,
+        updates: HashMap<String, Expression>,
         conditions: Result<Option<Condition>, SerializeError>,
     },
+    /// Rows of VALUES, whose values are mapped by position to `columns`, or to all the columns of the table
     Insert {
         table: String,
-        elements: InsertElement,
+        columns: Vec<String>,
+        rows: Vec<Vec<Expression>>,
+    },
+    /// Records returned by a query, whose columns are mapped by position to `columns`, or to all the columns of the table
+    InsertSelect {
+        table: String,
+        columns: Vec<String>,
+        query: Box<Command>,
     },
     Delete {
         table: String,
@@ -28,80 +77,506 @@ pub enum Command {
     },
 }
 
+fn deserialize_value(v: &Value) -> Result<Val, SerializeError> {
+    match v {
+        Value::Boolean(b) => Ok(Val::Boolean(*b)),
+        Value::Number(nb, _) => match (nb.parse::<i64>(), nb.parse::<f64>()) {
+            (Ok(i), _) => Ok(Val::Integer(i)),
+            (Err(_), Ok(f)) => Ok(Val::Float(f)),
+            _ => Err(SerializeError::NotImplemented(format!("Number not implemented : {}", nb))),
+        },
+        Value::SingleQuotedString(s)
+        | Value::DoubleQuotedString(s) => Ok(Val::Text(s.clone())),
+        Value::Null => Ok(Val::Null),
+        _ => Err(SerializeError::NotImplemented(format!("Type not implemented for the value : {:?}", v))),
+    }
+}
+
+/// Name of the column referenced by `expr`, qualified by its table when written `table.column`
+fn column_name(expr: &Expr) -> Option<String> {
+    match expr {
+        Expr::Identifier(ident) => Some(ident.value.clone()),
+        Expr::CompoundIdentifier(idents) => Some(idents.iter()
+            .map(|ident| ident.value.clone())
+            .collect::<Vec<String>>()
+            .join(".")),
+        // Window functions and aggregates are computed as columns of the grouped records
+        Expr::Function(func) if func.over.is_some() || aggregate_function(func).is_some() => Some(function_key(func)),
+        _ => None,
+    }
+}
+
+/// Name of the column computed for an aggregate or a window function, whatever the case its name is written in
+fn function_key(func: &Function) -> String {
+    let mut normalized = func.clone();
+    normalized.name = ObjectName(vec![Ident::new(func.name.to_string().to_uppercase())]);
+    normalized.to_string()
+}
+
+fn aggregate_function(func: &Function) -> Option<AggregateFunction> {
+    match func.name.to_string().to_uppercase().as_str() {
+        "COUNT" => Some(AggregateFunction::Count),
+        "SUM" => Some(AggregateFunction::Sum),
+        "AVG" => Some(AggregateFunction::Avg),
+        "MIN" => Some(AggregateFunction::Min),
+        "MAX" => Some(AggregateFunction::Max),
+        _ => None,
+    }
+}
+
+fn deserialize_aggregate(func: &Function) -> Result<Aggregate, SerializeError> {
+    let function = aggregate_function(func)
+    .ok_or(SerializeError::NotImplemented(format!("Function not implemented : {}", func)))?;
+    let argument = match func.args.as_slice() {
+        [FunctionArg::Unnamed(FunctionArgExpr::Wildcard)] if function == AggregateFunction::Count => None,
+        [FunctionArg::Unnamed(FunctionArgExpr::Expr(expr))] => Some(deserialize_expression(expr)?),
+        _ => return Err(SerializeError::NotImplemented(format!("Arguments not implemented for aggregate : {}", func))),
+    };
+    Ok(Aggregate { name: function_key(func), function, argument, distinct: func.distinct })
+}
+
+/// Calls `visit` on the function calls of `expr`.
+/// The expressions of a call, its arguments and its window, are visited as well when `visit` returns `true`.
+fn visit_functions<F>(expr: &Expr, visit: &mut F) -> Result<(), SerializeError>
+where F: FnMut(&Function) -> Result<bool, SerializeError> {
+    match expr {
+        Expr::Function(func) => {
+            if !visit(func)? {
+                return Ok(());
+            }
+            for arg in func.args.iter() {
+                if let FunctionArg::Unnamed(FunctionArgExpr::Expr(e)) = arg {
+                    visit_functions(e, visit)?;
+                }
+            }
+            if let Some(WindowType::WindowSpec(spec)) = &func.over {
+                for e in spec.partition_by.iter().chain(spec.order_by.iter().map(|o| &o.expr)) {
+                    visit_functions(e, visit)?;
+                }
+            }
+        },
+        Expr::BinaryOp { left, right, .. } => {
+            visit_functions(left, visit)?;
+            visit_functions(right, visit)?;
+        },
+        Expr::Nested(e) | Expr::UnaryOp { expr: e, .. } | Expr::Cast { expr: e, .. } => visit_functions(e, visit)?,
+        Expr::Case { operand, conditions, results, else_result } => for e in operand.iter()
+            .chain(else_result.iter())
+            .map(|e| e.as_ref())
+            .chain(conditions.iter())
+            .chain(results.iter()) {
+            visit_functions(e, visit)?;
+        },
+        _ => (),
+    }
+    Ok(())
+}
+
+/// Adds to `aggregates` the aggregates used in `expr` which are not listed yet.
+/// Windows are computed over the grouped records, so the ones of `expr` may be sorted or partitioned by aggregates.
+fn collect_aggregates(expr: &Expr, aggregates: &mut Vec<Aggregate>) -> Result<(), SerializeError> {
+    visit_functions(expr, &mut |func| {
+        if func.over.is_some() || aggregate_function(func).is_none() {
+            return Ok(true);
+        }
+        let agg = deserialize_aggregate(func)?;
+        if !aggregates.iter().any(|a| a.name == agg.name) {
+            aggregates.push(agg);
+        }
+        Ok(false)
+    })
+}
+
+/// Adds to `windows` the window functions used in `expr` which are not listed yet
+fn collect_windows(expr: &Expr, windows: &mut Vec<Window>) -> Result<(), SerializeError> {
+    visit_functions(expr, &mut |func| {
+        if func.over.is_none() {
+            return Ok(true);
+        }
+        let window = deserialize_window(func)?;
+        if !windows.iter().any(|w| w.name == window.name) {
+            windows.push(window);
+        }
+        Ok(false)
+    })
+}
+
+fn deserialize_window(func: &Function) -> Result<Window, SerializeError> {
+    let spec = match &func.over {
+        Some(WindowType::WindowSpec(spec)) => spec,
+        _ => return Err(SerializeError::NotImplemented(format!("Only windows written after OVER are implemented : {}", func))),
+    };
+    let name = func.name.to_string().to_uppercase();
+    let function = if aggregate_function(func).is_some() {
+        WindowFunction::Aggregate(deserialize_aggregate(func)?)
+    } else {
+        let args = func.args.iter()
+        .map(|arg| match arg {
+            FunctionArg::Unnamed(FunctionArgExpr::Expr(e)) => deserialize_expression(e),
+            _ => Err(SerializeError::NotImplemented(format!("Arguments not implemented for function : {}", func))),
+        })
+        .collect::<Result<Vec<Expression>, SerializeError>>()?;
+        let offset = |arg: Option<&Expression>| match arg {
+            None => Ok(1),
+            Some(Expression::Literal(Val::Integer(n))) if *n >= 0 => Ok(*n as usize),
+            Some(_) => Err(SerializeError::NotImplemented(format!("Offsets should be positive integers : {}", func))),
+        };
+        match (name.as_str(), args.as_slice()) {
+            ("ROW_NUMBER", []) => WindowFunction::RowNumber,
+            ("RANK", []) => WindowFunction::Rank,
+            ("DENSE_RANK", []) => WindowFunction::DenseRank,
+            ("LAG", [expr, rest @ ..]) if rest.len() <= 2 => WindowFunction::Lag {
+                expr: expr.clone(),
+                offset: offset(rest.first())?,
+                default: rest.get(1).cloned().unwrap_or(Expression::Literal(Val::Null)),
+            },
+            ("LEAD", [expr, rest @ ..]) if rest.len() <= 2 => WindowFunction::Lead {
+                expr: expr.clone(),
+                offset: offset(rest.first())?,
+                default: rest.get(1).cloned().unwrap_or(Expression::Literal(Val::Null)),
+            },
+            ("FIRST_VALUE", [expr]) => WindowFunction::FirstValue(expr.clone()),
+            _ => return Err(SerializeError::NotImplemented(format!("Window function not implemented : {}", func))),
+        }
+    };
+    Ok(Window {
+        name: function_key(func),
+        function,
+        partition_by: spec.partition_by.iter()
+        .map(deserialize_expression)
+        .collect::<Result<Vec<Expression>, SerializeError>>()?,
+        order_by: spec.order_by.iter()
+        .map(deserialize_order_by)
+        .collect::<Result<Vec<OrderBy>, SerializeError>>()?,
+        frame: deserialize_frame(&spec.window_frame)?,
+    })
+}
+
+/// Frame of a window, which spans from the start of the partition to the records sorted as equal to the current one by default
+fn deserialize_frame(frame: &Option<WindowFrame>) -> Result<Frame, SerializeError> {
+    let frame = match frame {
+        None => return Ok(Frame { rows: false, start: FrameBound::UnboundedPreceding, end: FrameBound::CurrentRow }),
+        Some(f) => f,
+    };
+    let bound = |bound: &WindowFrameBound| -> Result<FrameBound, SerializeError> {
+        Ok(match bound {
+            WindowFrameBound::CurrentRow => FrameBound::CurrentRow,
+            WindowFrameBound::Preceding(None) => FrameBound::UnboundedPreceding,
+            WindowFrameBound::Following(None) => FrameBound::UnboundedFollowing,
+            // RANGE offsets are amounts of the sorted value rather than of records
+            _ if frame.units == WindowFrameUnits::Range => return Err(SerializeError::NotImplemented(
+                String::from("RANGE frames only accept UNBOUNDED and CURRENT ROW bounds"),
+            )),
+            WindowFrameBound::Preceding(Some(n)) => FrameBound::Preceding(deserialize_row_count(n)?),
+            WindowFrameBound::Following(Some(n)) => FrameBound::Following(deserialize_row_count(n)?),
+        })
+    };
+    Ok(Frame {
+        rows: frame.units == WindowFrameUnits::Rows,
+        start: bound(&frame.start_bound)?,
+        end: match &frame.end_bound {
+            Some(end) => bound(end)?,
+            None => FrameBound::CurrentRow,
+        },
+    })
+}
+
+/// Name of the output column of an unaliased expression of the select list
+fn output_name(expr: &Expr) -> String {
+    match expr {
+        Expr::Identifier(ident) => ident.value.clone(),
+        Expr::CompoundIdentifier(idents) => idents.last().map(|ident| ident.value.clone()).unwrap_or_default(),
+        _ => expr.to_string(),
+    }
+}
+
+/// Value of a literal expression, negative numbers included
+fn literal(expr: &Expr) -> Option<Val> {
+    match expr {
+        Expr::Value(v) => deserialize_value(v).ok(),
+        Expr::UnaryOp { op: UnaryOperator::Minus, expr } => match literal(expr)? {
+            Val::Integer(i) => Some(Val::Integer(-i)),
+            Val::Float(f) => Some(Val::Float(-f)),
+            _ => None,
+        },
+        Expr::Nested(e) => literal(e),
+        _ => None,
+    }
+}
+
+fn arithmetic_operator(op: &BinaryOperator) -> Option<ArithmeticOperator> {
+    match op {
+        BinaryOperator::Plus => Some(ArithmeticOperator::Add),
+        BinaryOperator::Minus => Some(ArithmeticOperator::Subtract),
+        BinaryOperator::Multiply => Some(ArithmeticOperator::Multiply),
+        BinaryOperator::Divide => Some(ArithmeticOperator::Divide),
+        BinaryOperator::Modulo => Some(ArithmeticOperator::Modulo),
+        BinaryOperator::StringConcat => Some(ArithmeticOperator::Concat),
+        _ => None,
+    }
+}
+
+fn comparison_operator(op: &BinaryOperator) -> Option<ComparisonOperator> {
+    match op {
+        BinaryOperator::Eq => Some(ComparisonOperator::Equal),
+        BinaryOperator::NotEq => Some(ComparisonOperator::NotEqual),
+        BinaryOperator::Gt => Some(ComparisonOperator::GreaterThan),
+        BinaryOperator::GtEq => Some(ComparisonOperator::GreaterOrEqual),
+        BinaryOperator::Lt => Some(ComparisonOperator::LessThan),
+        BinaryOperator::LtEq => Some(ComparisonOperator::LessOrEqual),
+        _ => None,
+    }
+}
+
+/// Expression computing a value out of the columns of a record
+fn deserialize_expression(expr: &Expr) -> Result<Expression, SerializeError> {
+    if let Some(col) = column_name(expr) {
+        return Ok(Expression::Column(col));
+    }
+    if let Some(v) = literal(expr) {
+        return Ok(Expression::Literal(v));
+    }
+    match expr {
+        Expr::Nested(e) | Expr::UnaryOp { op: UnaryOperator::Plus, expr: e } => deserialize_expression(e),
+        Expr::UnaryOp { op: UnaryOperator::Minus, expr: e } => Ok(Expression::Negate(Box::new(deserialize_expression(e)?))),
+        Expr::BinaryOp { left, op, right } => match arithmetic_operator(op) {
+            Some(op) => Ok(Expression::BinaryOp {
+                left: Box::new(deserialize_expression(left)?),
+                op,
+                right: Box::new(deserialize_expression(right)?),
+            }),
+            None => condition_value(expr),
+        },
+        Expr::UnaryOp { op: UnaryOperator::Not, .. }
+        | Expr::IsNull(_)
+        | Expr::IsNotNull(_)
+        | Expr::InList { .. }
+        | Expr::Like { .. }
+        | Expr::ILike { .. }
+        | Expr::Between { .. }
+        | Expr::InSubquery { .. }
+        | Expr::Exists { .. } => condition_value(expr),
+        Expr::Function(func) if func.name.to_string().to_uppercase() == "REGEXP_LIKE" => condition_value(expr),
+        Expr::Function(func) => function_call(
+            &func.name.to_string(),
+            func.args.iter()
+            .map(|arg| match arg {
+                FunctionArg::Unnamed(FunctionArgExpr::Expr(e)) => deserialize_expression(e),
+                _ => Err(SerializeError::NotImplemented(format!("Arguments not implemented for function : {}", func))),
+            })
+            .collect::<Result<Vec<Expression>, SerializeError>>()?,
+        ),
+        Expr::Trim { expr, trim_where, trim_what } => {
+            let name = match trim_where {
+                Some(TrimWhereField::Leading) => "LTRIM",
+                Some(TrimWhereField::Trailing) => "RTRIM",
+                Some(TrimWhereField::Both) | None => "TRIM",
+            };
+            let mut args = vec![deserialize_expression(expr)?];
+            if let Some(what) = trim_what {
+                args.push(deserialize_expression(what)?);
+            }
+            function_call(name, args)
+        },
+        Expr::Substring { expr, substring_from, substring_for, .. } => {
+            let mut args = vec![
+                deserialize_expression(expr)?,
+                match substring_from {
+                    Some(from) => deserialize_expression(from)?,
+                    None => Expression::Literal(Val::Integer(1)),
+                },
+            ];
+            if let Some(length) = substring_for {
+                args.push(deserialize_expression(length)?);
+            }
+            function_call("SUBSTR", args)
+        },
+        Expr::Ceil { expr, field: DateTimeField::NoDateTime } => function_call("CEIL", vec![deserialize_expression(expr)?]),
+        Expr::Floor { expr, field: DateTimeField::NoDateTime } => function_call("FLOOR", vec![deserialize_expression(expr)?]),
+        Expr::Case { operand, conditions, results, else_result } => {
+            let mut branches = vec![];
+            for (when, then) in conditions.iter().zip(results.iter()) {
+                // The simple form compares the operand to each value
+                let cond = match operand {
+                    Some(op) => Condition::comparison(deserialize_expression(op)?, ComparisonOperator::Equal, deserialize_expression(when)?),
+                    None => Some(when.clone()).deserialize_conditions()?.ok_or(SerializeError::NotImplementable)?,
+                };
+                branches.push((cond, deserialize_expression(then)?));
+            }
+            let otherwise = match else_result {
+                Some(e) => Some(Box::new(deserialize_expression(e)?)),
+                None => None,
+            };
+            Ok(Expression::Case { branches, otherwise })
+        },
+        Expr::Extract { field, expr } => function_call("DATE_PART", vec![
+            Expression::Literal(Val::Text(field.to_string().to_lowercase())),
+            deserialize_expression(expr)?,
+        ]),
+        Expr::Interval(interval) => {
+            let amount = match literal(&interval.value) {
+                Some(Val::Null) | None => return Err(SerializeError::NotImplemented(format!("Intervals should be literals : {}", interval))),
+                Some(v) => v.to_string(),
+            };
+            let written = match interval.leading_field {
+                Some(unit) => format!("{} {}", amount, unit),
+                None => amount,
+            };
+            Interval::parse(&written)
+            .map(|i| Expression::Literal(Val::Interval(i)))
+            .ok_or(SerializeError::NotImplemented(format!("Interval not implemented : {}", interval)))
+        },
+        Expr::TypedString { data_type, value } => Ok(Expression::Cast(
+            Box::new(Expression::Literal(Val::Text(value.clone()))),
+            cast_type(data_type)?,
+        )),
+        Expr::Cast { expr, data_type } => Ok(Expression::Cast(Box::new(deserialize_expression(expr)?), cast_type(data_type)?)),
+        Expr::Subquery(query) => Ok(Expression::Subquery(deserialize_subquery(query)?)),
+        _ => Err(SerializeError::NotImplemented(format!("Expression not implemented : {}", expr))),
+    }
+}
+
+/// Truth value of the condition `expr` states
+fn condition_value(expr: &Expr) -> Result<Expression, SerializeError> {
+    let cond = Some(expr.clone()).deserialize_conditions()?.ok_or(SerializeError::NotImplementable)?;
+    Ok(Expression::Condition(Box::new(cond)))
+}
+
+/// SELECT nested in another statement
+fn deserialize_subquery(query: &Query) -> Result<Subquery, SerializeError> {
+    match Statement::Query(Box::new(query.clone())).deserialize_as_command()? {
+        command @ (Command::Select { .. } | Command::SetOperation { .. } | Command::With { .. }) => Ok(Subquery { sql: query.to_string(), command: Rc::new(command) }),
+        _ => Err(SerializeError::NotImplementable),
+    }
+}
+
+/// Call of a function of the registry, checked to exist and to accept the arguments
+fn function_call(name: &str, args: Vec<Expression>) -> Result<Expression, SerializeError> {
+    let function = functions::lookup(name)
+    .ok_or(SerializeError::NotImplemented(format!("Function not implemented : {}", name)))?;
+    if !function.accepts(args.len()) {
+        return Err(SerializeError::NotImplemented(format!("Function {} does not accept {} arguments", function.name, args.len())));
+    }
+    Ok(Expression::Function { name: function.name.to_string(), args })
+}
+
+fn cast_type(data_type: &DataType) -> Result<ColumnType, SerializeError> {
+    match data_type {
+        DataType::Int(_) | DataType::Integer(_) | DataType::BigInt(_) | DataType::SmallInt(_) | DataType::TinyInt(_)
+        | DataType::Int8(_) => Ok(ColumnType::Integer),
+        DataType::Float(_) | DataType::Float4 | DataType::Float8 | DataType::Real | DataType::Double
+        | DataType::DoublePrecision | DataType::Numeric(_) | DataType::Decimal(_) | DataType::Dec(_) => Ok(ColumnType::Float),
+        DataType::Bool | DataType::Boolean => Ok(ColumnType::Boolean),
+        DataType::Text | DataType::String | DataType::Varchar(_) | DataType::Char(_) | DataType::Character(_)
+        | DataType::CharacterVarying(_) | DataType::CharVarying(_) | DataType::Nvarchar(_) => Ok(ColumnType::Text),
+        DataType::Date => Ok(ColumnType::Date),
+        DataType::Timestamp(_, _) | DataType::Datetime(_) => Ok(ColumnType::Timestamp),
+        _ => Err(SerializeError::NotImplemented(format!("Type not implemented for CAST : {}", data_type))),
+    }
+}
+
+fn text_operand(expr: &Expr) -> Result<String, SerializeError> {
+    match literal(expr) {
+        Some(Val::Text(s)) => Ok(s),
+        _ => Err(SerializeError::NotImplemented(format!("Patterns should be strings : {}", expr))),
+    }
+}
+
+fn deserialize_like(expr: &Expr, pattern: &Expr, escape: Option<char>, case_insensitive: bool) -> Result<Condition, SerializeError> {
+    Ok(Condition::Like { expr: deserialize_expression(expr)?, pattern: text_operand(pattern)?, escape, case_insensitive })
+}
+
+fn deserialize_regex(expr: &Expr, pattern: &Expr, case_insensitive: bool) -> Result<Condition, SerializeError> {
+    let pattern = text_operand(pattern)?;
+    // The flag is kept in the pattern, so that conditions on the same regular expression are equal
+    let flags = if case_insensitive { "(?i)" } else { "" };
+    let re = Regex::new(&format!("{}{}", flags, pattern))
+    .map_err(|e| SerializeError::NotImplemented(format!("Invalid regular expression {} : {}", pattern, e)))?;
+    Ok(Condition::Regex(deserialize_expression(expr)?, re))
+}
+
+/// `cond`, or its negation when `negated` is set
+fn negated_if(cond: Condition, negated: bool) -> Condition {
+    if negated {
+        Condition::Not(Box::new(cond))
+    } else {
+        cond
+    }
+}
+
+fn deserialize_row_count(expr: &Expr) -> Result<usize, SerializeError> {
+    match expr {
+        Expr::Value(Value::Number(n, _)) => n.parse::<usize>()
+        .map_err(|_| SerializeError::NotImplemented(format!("Invalid amount of rows : {}", n))),
+        _ => Err(SerializeError::NotImplemented(format!("Amount of rows should be a positive integer : {}", expr))),
+    }
+}
+
+fn deserialize_order_by(order_by: &OrderByExpr) -> Result<OrderBy, SerializeError> {
+    let key = match &order_by.expr {
+        Expr::Value(Value::Number(_, _)) => match deserialize_row_count(&order_by.expr)? {
+            0 => return Err(SerializeError::NotImplemented(String::from("Positions in ORDER BY start at 1"))),
+            position => SortKey::Position(position),
+        },
+        expr => SortKey::Expression(deserialize_expression(expr)?),
+    };
+    let ascending = order_by.asc.unwrap_or(true);
+    // As in PostgreSQL, NULL is sorted as if it were greater than any value
+    let nulls_first = order_by.nulls_first.unwrap_or(!ascending);
+    Ok(OrderBy { key, ascending, nulls_first })
+}
+
+fn deserialize_table_factor(factor: &TableFactor) -> Result<TableReference, SerializeError> {
+    match factor {
+        TableFactor::Table { name, alias, .. } => Ok(TableReference {
+            name: name.0.iter()
+            .map(|ident| ident.value.clone())
+            .collect::<Vec<String>>()
+            .join("."),
+            alias: alias.as_ref().map(|a| a.name.value.clone()),
+        }),
+        _ => Err(SerializeError::NotImplemented(String::from("Only tables can be listed in FROM clause"))),
+    }
+}
+
+fn deserialize_join_constraint(constraint: &SqlJoinConstraint) -> Result<JoinConstraint, SerializeError> {
+    match constraint {
+        SqlJoinConstraint::On(expr) => match Some(expr.clone()).deserialize_conditions()? {
+            Some(cond) => Ok(JoinConstraint::On(cond)),
+            None => Ok(JoinConstraint::None),
+        },
+        SqlJoinConstraint::Using(idents) => Ok(JoinConstraint::Using(idents.iter()
+            .map(|ident| ident.value.clone())
+            .collect())),
+        SqlJoinConstraint::Natural => Ok(JoinConstraint::Natural),
+        SqlJoinConstraint::None => Ok(JoinConstraint::None),
+    }
+}
+
+fn deserialize_joined_tables(table: &TableWithJoins) -> Result<JoinedTables, SerializeError> {
+    let mut joins = vec![];
+    for j in table.joins.iter() {
+        let (kind, constraint) = match &j.join_operator {
+            JoinOperator::Inner(c) => (JoinKind::Inner, deserialize_join_constraint(c)?),
+            JoinOperator::LeftOuter(c) => (JoinKind::LeftOuter, deserialize_join_constraint(c)?),
+            JoinOperator::RightOuter(c) => (JoinKind::RightOuter, deserialize_join_constraint(c)?),
+            JoinOperator::FullOuter(c) => (JoinKind::FullOuter, deserialize_join_constraint(c)?),
+            JoinOperator::CrossJoin => (JoinKind::Cross, JoinConstraint::None),
+            _ => return Err(SerializeError::NotImplemented(format!("Join not implemented : {}", j))),
+        };
+        joins.push(Join { table: deserialize_table_factor(&j.relation)?, kind, constraint });
+    }
+    Ok(JoinedTables { table: deserialize_table_factor(&table.relation)?, joins })
+}
+
 impl Filtering for Option<Expr> {
     fn deserialize_conditions(&self) -> Result<Option<Condition>, SerializeError> {
         match self {
             Some(Expr::BinaryOp { left, op, right }) => match op {
-                BinaryOperator::Eq => match (left.deref(), right.deref()) {
-                    (
-                        Expr::Identifier(ident),
-                        Expr::Value(Value::SingleQuotedString(s))
-                    ) |
-                    (
-                        Expr::Value(Value::SingleQuotedString(s)),
-                        Expr::Identifier(ident),
-                    ) => Ok(Some(Condition::Equal(ident.value.clone(), s.clone()))),
-                    (
-                        Expr::Identifier(ident),
-                        Expr::Value(Value::DoubleQuotedString(s))
-                    ) |
-                    (
-                        Expr::Value(Value::DoubleQuotedString(s)),
-                        Expr::Identifier(ident),
-                    ) => Ok(Some(Condition::Equal(ident.value.clone(), s.clone()))),
-                    (
-                        Expr::Identifier(ident),
-                        Expr::Value(Value::Number(n2, _))
-                    ) => Ok(Some(Condition::Equal(ident.value.clone(), n2.clone()))),
-                    _ => Err(SerializeError::NotImplemented(String::from("Types not compatible for comparison with operator '='"))),
-                },
-                BinaryOperator::Gt => match (left.deref(), right.deref()) {
-                    (
-                        Expr::Identifier(ident),
-                        Expr::Value(Value::SingleQuotedString(s))
-                    ) |
-                    (
-                        Expr::Value(Value::SingleQuotedString(s)),
-                        Expr::Identifier(ident),
-                    ) => Ok(Some(Condition::GreaterThan(ident.value.clone(), s.clone()))),
-                    (
-                        Expr::Identifier(ident),
-                        Expr::Value(Value::DoubleQuotedString(s))
-                    ) |
-                    (
-                        Expr::Value(Value::DoubleQuotedString(s)),
-                        Expr::Identifier(ident),
-                    ) => Ok(Some(Condition::GreaterThan(ident.value.clone(), s.clone()))),
-                    (
-                        Expr::Identifier(ident),
-                        Expr::Value(Value::Number(n2, _))
-                    ) => Ok(Some(Condition::GreaterThan(ident.value.clone(), n2.clone()))),
-                    _ =>
-                        Err(SerializeError::NotImplemented(String::from("Types not compatible for comparison with operator '>'"))),
-                },
-                BinaryOperator::Lt => match (left.deref(), right.deref()) {
-                    (
-                        Expr::Identifier(ident),
-                        Expr::Value(Value::SingleQuotedString(s))
-                    ) |
-                    (
-                        Expr::Value(Value::SingleQuotedString(s)),
-                        Expr::Identifier(ident),
-                    ) => Ok(Some(Condition::LessThan(ident.value.clone(), s.clone()))),
-                    (
-                        Expr::Identifier(ident),
-                        Expr::Value(Value::DoubleQuotedString(s))
-                    ) |
-                    (
-                        Expr::Value(Value::DoubleQuotedString(s)),
-                        Expr::Identifier(ident),
-                    ) => Ok(Some(Condition::LessThan(ident.value.clone(), s.clone()))),
-                    (
-                        Expr::Identifier(ident),
-                        Expr::Value(Value::Number(n2, _))
-                    ) => Ok(Some(Condition::LessThan(ident.value.clone(), n2.clone()))),
-                    _ => Err(SerializeError::NotImplemented(String::from(format!("Expression '{:?}' can not be used for conditions", self)))),
-                },
+                BinaryOperator::PGRegexMatch => Ok(Some(deserialize_regex(left, right, false)?)),
+                BinaryOperator::PGRegexIMatch => Ok(Some(deserialize_regex(left, right, true)?)),
+                BinaryOperator::PGRegexNotMatch => Ok(Some(negated_if(deserialize_regex(left, right, false)?, true))),
+                BinaryOperator::PGRegexNotIMatch => Ok(Some(negated_if(deserialize_regex(left, right, true)?, true))),
                 BinaryOperator::And => match (
                     Self::deserialize_conditions(&Some(left.deref().clone()))?,
                     Self::deserialize_conditions(&Some(right.deref().clone()))?
@@ -126,119 +601,236 @@ impl Filtering for Option<Expr> {
                     (Some(cond), None) => Ok(Some(cond)),
                     (None, None) => Ok(None),
                 },
-                _ => Err(SerializeError::NotImplemented(String::from("Combination of comparisons not compatible with operator 'or'")))
+                op => match comparison_operator(op) {
+                    Some(cmp) => Ok(Some(Condition::comparison(deserialize_expression(left)?, cmp, deserialize_expression(right)?))),
+                    None if arithmetic_operator(op).is_some() => Ok(Some(Condition::Truth(deserialize_expression(&Expr::BinaryOp {
+                        left: left.clone(),
+                        op: op.clone(),
+                        right: right.clone(),
+                    })?))),
+                    None => Err(SerializeError::NotImplemented(format!("Operator not implemented in conditions : {}", op))),
+                },
+            },
+            Some(Expr::Nested(e)) => Self::deserialize_conditions(&Some(e.deref().clone())),
+            Some(Expr::UnaryOp { op: UnaryOperator::Not, expr }) => Ok(
+                Self::deserialize_conditions(&Some(expr.deref().clone()))?
+                .map(|cond| Condition::Not(Box::new(cond)))
+            ),
+            Some(Expr::IsNull(e)) => Ok(Some(Condition::IsNull(deserialize_expression(e)?))),
+            Some(Expr::IsNotNull(e)) => Ok(Some(negated_if(Condition::IsNull(deserialize_expression(e)?), true))),
+            Some(Expr::InList { expr, list, negated }) => Ok(Some(negated_if(
+                Condition::In(
+                    deserialize_expression(expr)?,
+                    list.iter().map(deserialize_expression).collect::<Result<Vec<Expression>, SerializeError>>()?,
+                ),
+                *negated,
+            ))),
+            Some(Expr::Like { negated, expr, pattern, escape_char }) => Ok(Some(negated_if(
+                deserialize_like(expr, pattern, *escape_char, false)?,
+                *negated,
+            ))),
+            Some(Expr::ILike { negated, expr, pattern, escape_char }) => Ok(Some(negated_if(
+                deserialize_like(expr, pattern, *escape_char, true)?,
+                *negated,
+            ))),
+            Some(Expr::Function(func)) if func.name.to_string().to_uppercase() == "REGEXP_LIKE" => match func.args.as_slice() {
+                [
+                    FunctionArg::Unnamed(FunctionArgExpr::Expr(expr)),
+                    FunctionArg::Unnamed(FunctionArgExpr::Expr(pattern)),
+                ] => Ok(Some(deserialize_regex(expr, pattern, false)?)),
+                _ => Err(SerializeError::NotImplemented(format!("REGEXP_LIKE expects a column and a pattern : {}", func))),
             },
+            Some(Expr::Between { expr, negated, low, high }) => Ok(Some(negated_if(
+                Condition::Between(deserialize_expression(expr)?, deserialize_expression(low)?, deserialize_expression(high)?),
+                *negated,
+            ))),
+            Some(Expr::InSubquery { expr, subquery, negated }) => Ok(Some(negated_if(
+                Condition::InSubquery(deserialize_expression(expr)?, deserialize_subquery(subquery)?),
+                *negated,
+            ))),
+            Some(Expr::Exists { subquery, negated }) => Ok(Some(negated_if(Condition::Exists(deserialize_subquery(subquery)?), *negated))),
             None => Ok(None),
-            _ => Err(SerializeError::NotImplemented(String::from("Types not compatible for comparison"))),
+            // Any other expression holds when its value is true, as a boolean column
+            Some(expr) => Ok(Some(Condition::Truth(deserialize_expression(expr)?))),
         }
     }
 }
 
+/// SELECT, as written without ORDER BY, LIMIT nor OFFSET
+fn deserialize_select(select: &Select) -> Result<Command, SerializeError> {
+    let mut projections = vec![];
+    let mut tables = vec![];
+    let mut group_by = vec![];
+    let mut aggregates = vec![];
+    let mut windows = vec![];
+    let distinct = match &select.distinct {
+        None => None,
+        Some(SqlDistinct::Distinct) => Some(Distinct::Rows),
+        Some(SqlDistinct::On(exprs)) => Some(Distinct::On(exprs.iter()
+            .map(deserialize_expression)
+            .collect::<Result<Vec<Expression>, SerializeError>>()?)),
+    };
+    for proj in select.projection.iter() {
+        projections.push(match proj {
+            SelectItem::UnnamedExpr(expr) => {
+                collect_aggregates(expr, &mut aggregates)?;
+                collect_windows(expr, &mut windows)?;
+                Projection::Expression(deserialize_expression(expr)?, output_name(expr))
+            },
+            SelectItem::ExprWithAlias { expr, alias } => {
+                collect_aggregates(expr, &mut aggregates)?;
+                collect_windows(expr, &mut windows)?;
+                Projection::Expression(deserialize_expression(expr)?, alias.value.clone())
+            },
+            SelectItem::QualifiedWildcard(name, _) => Projection::Wildcard(Some(name.0.iter()
+                .map(|ident| ident.value.clone())
+                .collect::<Vec<String>>()
+                .join("."))),
+            SelectItem::Wildcard(_) => Projection::Wildcard(None),
+        });
+    }
+    for t in &select.from {
+        tables.push(deserialize_joined_tables(t)?);
+    }
+    match &select.group_by {
+        GroupByExpr::Expressions(exprs) => for expr in exprs.iter() {
+            group_by.push(deserialize_expression(expr)?);
+        },
+        GroupByExpr::All => return Err(SerializeError::NotImplemented(String::from("GROUP BY ALL is not implemented"))),
+    }
+    if let Some(expr) = &select.having {
+        collect_aggregates(expr, &mut aggregates)?;
+    }
+    Ok(Command::Select {
+        tables,
+        projections,
+        distinct,
+        conditions: select.selection.deserialize_conditions(),
+        group_by,
+        aggregates,
+        having: select.having.deserialize_conditions(),
+        windows,
+        order_by: vec![],
+        limit: None,
+        offset: 0,
+    })
+}
+
+/// Body of a query : a SELECT, a query between parentheses, or queries combined by UNION, INTERSECT or EXCEPT
+fn deserialize_set_expr(body: &SetExpr) -> Result<Command, SerializeError> {
+    match body {
+        SetExpr::Select(select) => deserialize_select(select),
+        SetExpr::Query(query) => deserialize_query(query),
+        SetExpr::SetOperation { op, set_quantifier, left, right } => Ok(Command::SetOperation {
+            operator: match op {
+                SqlSetOperator::Union => SetOperator::Union,
+                SqlSetOperator::Intersect => SetOperator::Intersect,
+                SqlSetOperator::Except => SetOperator::Except,
+            },
+            all: match set_quantifier {
+                SetQuantifier::All => true,
+                SetQuantifier::Distinct | SetQuantifier::None => false,
+                SetQuantifier::ByName | SetQuantifier::AllByName => return Err(SerializeError::NotImplemented(
+                    format!("Set operations by name are not implemented : {}", body),
+                )),
+            },
+            left: Box::new(deserialize_set_expr(left)?),
+            right: Box::new(deserialize_set_expr(right)?),
+            order_by: vec![],
+            limit: None,
+            offset: 0,
+        }),
+        _ => Err(SerializeError::UselessToImplement),
+    }
+}
+
+/// Query whose rows are sorted and limited as its ORDER BY, LIMIT and OFFSET state, preceded by the common tables of its WITH clause
+fn deserialize_query(query: &Query) -> Result<Command, SerializeError> {
+    let command = deserialize_sorted_query(query)?;
+    let with = match &query.with {
+        Some(with) => with,
+        None => return Ok(command),
+    };
+    let tables = with.cte_tables.iter()
+    .map(|cte| Ok(CommonTable {
+        name: cte.alias.name.value.clone(),
+        columns: cte.alias.columns.iter().map(|col| col.value.clone()).collect(),
+        query: deserialize_query(&cte.query)?,
+    }))
+    .collect::<Result<Vec<CommonTable>, SerializeError>>()?;
+    Ok(Command::With { recursive: with.recursive, tables, query: Box::new(command) })
+}
+
+/// Body of a query, sorted and limited as its ORDER BY, LIMIT and OFFSET state
+fn deserialize_sorted_query(query: &Query) -> Result<Command, SerializeError> {
+    let mut command = deserialize_set_expr(&query.body)?;
+    let sorting = query.order_by.iter()
+    .map(deserialize_order_by)
+    .collect::<Result<Vec<OrderBy>, SerializeError>>()?;
+    let skipped = match &query.offset {
+        Some(o) => deserialize_row_count(&o.value)?,
+        None => 0,
+    };
+    let limited = match (&query.limit, &query.fetch) {
+        (Some(expr), None) => Some(deserialize_row_count(expr)?),
+        (None, Some(fetch)) if !fetch.percent && !fetch.with_ties => match &fetch.quantity {
+            Some(expr) => Some(deserialize_row_count(expr)?),
+            None => Some(1),
+        },
+        (None, None) => None,
+        _ => return Err(SerializeError::NotImplemented(String::from("Only one of LIMIT or FETCH FIRST n ROWS ONLY can be used"))),
+    };
+    // Aggregates and windows only used to sort the records are computed along the selected ones
+    if let Command::Select { aggregates, windows, .. } = &mut command {
+        for o in query.order_by.iter() {
+            collect_aggregates(&o.expr, aggregates)?;
+            collect_windows(&o.expr, windows)?;
+        }
+    }
+    if sorting.is_empty() && skipped == 0 && limited.is_none() {
+        return Ok(command);
+    }
+    match &mut command {
+        Command::Select { order_by, limit, offset, .. } | Command::SetOperation { order_by, limit, offset, .. }
+        if order_by.is_empty() && *offset == 0 && limit.is_none() => {
+            *order_by = sorting;
+            *offset = skipped;
+            *limit = limited;
+            Ok(command)
+        },
+        _ => Err(SerializeError::NotImplemented(format!("Query sorted or limited twice : {}", query))),
+    }
+}
+
 impl Executable for Statement {
     fn deserialize_as_command(&self) -> Result<Command, SerializeError> {
         match self {
-            Statement::Query(q) => {
-                let query = q.deref();
-                let body = query.body.deref();
-                let mut columns: Vec<String> = vec![];
-                let mut all_selected = false;
-                let mut tables = vec![];
-                // let conditions = 
-                let mut _conditions = Ok(None);
-                match body {
-                    SetExpr::Select(s) => {
-                        let select = s.deref();
-                        _conditions = s.selection.deserialize_conditions();
-                        for proj in select.projection.iter() {
-                            match proj {
-                                SelectItem::UnnamedExpr(Expr::Identifier(ident)) => columns.push(ident.clone().value),
-                                SelectItem::Wildcard(_) => all_selected = true,
-                                _ => return Err(SerializeError::NotImplemented(String::from("Only identifiers and aliases are implemented")))
-                            }
-                        }
-                        for t in &select.from {
-                            if let TableFactor::Table { name, .. } = &t.relation {
-                                let idents = &name.0;
-                                idents.iter()
-                                .for_each(|ident| tables.push(ident.value.clone()))
-                            }
-                        }
-                    },
-                    _ => return Err(SerializeError::UselessToImplement)
-                }
-                // Ok(Command::Select { table, columns, conditions })
-                if all_selected {
-                    Ok(Command::Select { tables, columns: Columns::All, conditions: _conditions })
-                } else {
-                    Ok(Command::Select { tables, columns: Columns::ColumnNames(columns), conditions: _conditions })
-                }
-            },
+            Statement::Query(q) => deserialize_query(q),
             Statement::Insert {
                 table_name,
                 columns,
                 source,
                 ..
             } => {
-                let _ident = table_name.0.get(0).ok_or(SerializeError::NotImplementable)?;
+                let _ident = table_name.0.first().ok_or(SerializeError::NotImplementable)?;
                 let table = _ident.value.clone();
-                let mut _elements: InsertElement = InsertElement::PlainValues(vec![]);
-                if columns.len() == 0 {
-                    let mut plain_values: Vec<Val> = Vec::new();
-                    match source.deref().body.deref() {
-                        SetExpr::Values(vals) => {
-                            let unique_line = vals.rows.first().ok_or(SerializeError::NotImplementable)?;
-                            for expr in unique_line.iter() {
-                                match expr {
-                                    Expr::Value(v) => match v {
-                                        Value::Boolean(b) => if *b {
-                                            plain_values.push(Some(String::from("true")));
-                                        } else {
-                                            plain_values.push(Some(String::from("false")));
-                                        },
-                                        Value::Number(nb, _) => plain_values.push(Some(nb.clone())),
-                                        Value::SingleQuotedString(s)
-                                        | Value::DoubleQuotedString(s) => plain_values.push(Some(s.clone())),
-                                        Value::Null => plain_values.push(None),
-                                        _ => return Err(SerializeError::NotImplemented(String::from(format!("Type not implemented for the value : {:?}", v)))),
-                                    },
-                                    _ => return Err(SerializeError::NotImplementable),
-                                }
-                            }
-                        },
-                        _ => return Err(SerializeError::NotImplementable),
-                    }
-                    _elements = InsertElement::PlainValues(plain_values);       
-                } else {
-                    let mut mapped_values: HashMap<String, Val> = HashMap::new();
-                    match source.deref().body.deref() {
-                            SetExpr::Values(vals) => {
-                                let unique_line = vals.rows.first().ok_or(SerializeError::NotImplementable)?;
-                                for i in 0..unique_line.len() {
-                                    match (columns.get(i), unique_line.get(i)) {
-                                        (Some(ident), Some(expr)) => match expr {
-                                            Expr::Value(v) => match v {
-                                                Value::Boolean(b) => if *b {
-                                                    mapped_values.insert(ident.value.clone(), Some(String::from("true")));
-                                                } else {
-                                                    mapped_values.insert(ident.value.clone(), Some(String::from("false")));
-                                                },
-                                                Value::Number(nb, _) => { mapped_values.insert(ident.value.clone(), Some(nb.clone())); },
-                                                Value::SingleQuotedString(s)
-                                                | Value::DoubleQuotedString(s) => {mapped_values.insert(ident.value.clone(), Some(s.clone()));},
-                                                Value::Null =>{ mapped_values.insert(ident.value.clone(), None); },
-                                                _ => return Err(SerializeError::NotImplemented(String::from(format!("Type not implemented for the value : {:?}", v)))),
-                                            },
-                                            _ => return Err(SerializeError::NotImplementable),
-                                        },
-                                        _ => return Err(SerializeError::NotImplementable),
-                                    }
-                                }
-                            },
-                            _ => return Err(SerializeError::NotImplementable),
-                    }
-                    _elements = InsertElement::MappedValues(mapped_values);
-                }
-                Ok(Command::Insert { table, elements: _elements })
+                let rows = match source.deref().body.deref() {
+                    SetExpr::Values(vals) => &vals.rows,
+                    _ => return Ok(Command::InsertSelect {
+                        table,
+                        columns: columns.iter().map(|ident| ident.value.clone()).collect(),
+                        query: Box::new(deserialize_query(source)?),
+                    }),
+                };
+                Ok(Command::Insert {
+                    table,
+                    columns: columns.iter().map(|ident| ident.value.clone()).collect(),
+                    rows: rows.iter()
+                    .map(|row| row.iter()
+                        .map(deserialize_expression)
+                        .collect::<Result<Vec<Expression>, SerializeError>>())
+                    .collect::<Result<Vec<Vec<Expression>>, SerializeError>>()?,
+                })
             },
             Statement::Update {
                 table,
@@ -248,7 +840,7 @@ impl Executable for Statement {
             } => {
                 let table = if let TableFactor::Table { name, .. } = &table.relation {
                     Ok(name.0
-                    .get(0)
+                    .first()
                     .ok_or(SerializeError::NotImplementable)?
                     .value
                     .clone())
@@ -259,21 +851,7 @@ impl Executable for Statement {
                 let mut updates = HashMap::new();
                 for ass in assignments.iter() {
                     let attr_name = ass.id.first().ok_or(SerializeError::NotImplementable)?.value.clone();
-                    match &ass.value {
-                        Expr::Value(v) => match v {
-                            Value::Boolean(b) => if *b {
-                                updates.insert(attr_name, Some(String::from("true")));
-                            } else {
-                                updates.insert(attr_name, Some(String::from("false")));
-                            },
-                            Value::Number(nb, _) => { updates.insert(attr_name, Some(nb.clone())); },
-                            Value::SingleQuotedString(s)
-                            | Value::DoubleQuotedString(s) => { updates.insert(attr_name, Some(s.clone())); },
-                            Value::Null => { updates.insert(attr_name, None); },
-                            _ => return Err(SerializeError::NotImplemented(String::from(format!("Type not implemented for the value : {:?}", &ass.value)))),
-                        },
-                        _ => return Err(SerializeError::NotImplementable),
-                    }
+                    updates.insert(attr_name, deserialize_expression(&ass.value)?);
                 } 
             Ok(Command::Update {table, updates, conditions })
             },
@@ -0,0 +1,701 @@
+use std::{cell::RefCell, collections::{hash_map::Entry, HashMap}, fmt::Display, rc::Rc};
+
+use sqlparser::{
+    ast::{BinaryOperator, Expr, Statement},
+    dialect::{Dialect, GenericDialect},
+    keywords::Keyword,
+    parser::{Parser, ParserError},
+    tokenizer::{Token, Tokenizer},
+};
+
+use crate::{
+    commands::Command,
+    entities::{DataStore, Directory, Record, Table},
+    errors::{ExecutionError, QueryError},
+    traits::{
+        Aggregate, Binding, Columns, CommonTable, Condition, Distinct, Executable, Expression, InsertElement, JoinConstraint, OrderBy, Projection,
+        Queryable, Recordable, Reference, SetOperator, SortKey, Storage, Subquery, Window, WindowFunction,
+    },
+    utils::Value,
+};
+
+/// Outcome of a single SQL statement.
+#[derive(Debug, PartialEq)]
+pub enum QueryResult {
+    Rows {
+        headers: Vec<String>,
+        rows: Vec<Vec<Value>>,
+    },
+    AffectedRows(usize),
+}
+
+impl Display for QueryResult {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            QueryResult::Rows { headers, rows } => {
+                let cells: Vec<Vec<String>> = rows.iter()
+                .map(|r| r.iter()
+                    .map(|v| v.to_string())
+                    .collect())
+                .collect();
+                let widths: Vec<usize> = headers.iter()
+                .enumerate()
+                .map(|(idx, h)| cells.iter()
+                    .filter_map(|r| r.get(idx))
+                    .map(|c| c.chars().count())
+                    .chain([h.chars().count()])
+                    .max()
+                    .unwrap_or(0))
+                .collect();
+                let format_line = |line: &[String]| -> String {
+                    line.iter()
+                    .zip(widths.iter())
+                    .map(|(c, w)| format!(" {}{} ", c, " ".repeat(w - c.chars().count())))
+                    .collect::<Vec<String>>()
+                    .join("|")
+                };
+                writeln!(f, "{}", format_line(headers))?;
+                writeln!(f, "{}", widths.iter()
+                    .map(|w| "-".repeat(w + 2))
+                    .collect::<Vec<String>>()
+                    .join("+"))?;
+                for r in cells.iter() {
+                    writeln!(f, "{}", format_line(r))?;
+                }
+                match rows.len() {
+                    1 => write!(f, "(1 row)"),
+                    n => write!(f, "({} rows)", n),
+                }
+            },
+            QueryResult::AffectedRows(1) => write!(f, "1 row affected"),
+            QueryResult::AffectedRows(n) => write!(f, "{} rows affected", n),
+        }
+    }
+}
+
+/// `expr`, or the expression of the output column it names when it is not a column of `source`
+fn output_expression(source: &Table<Record>, outputs: &[(String, Expression)], expr: &Expression) -> Expression {
+    match expr {
+        Expression::Column(col) if source.find_column(col).is_none() => outputs.iter()
+        .find(|(name, _)| name == col)
+        .map(|(_, e)| e.clone())
+        .unwrap_or(expr.clone()),
+        _ => expr.clone(),
+    }
+}
+
+/// First column read once per group by `exprs` or `having`, which is neither a grouping key of `source` nor computed over the groups
+fn ungrouped_column<'a>(
+    source: &Table<Record>,
+    keys: &[Expression],
+    aggregates: &[Aggregate],
+    windows: &[Window],
+    mut exprs: impl Iterator<Item = &'a Expression>,
+    having: Option<&'a Condition>,
+) -> Option<&'a str> {
+    let same_column = |col: &str, key: &str| col == key || source.find_column(col).is_some_and(|idx| source.find_column(key) == Some(idx));
+    let known = |e: &Expression| keys.contains(e) || match e {
+        Expression::Column(col) => aggregates.iter().any(|a| &a.name == col)
+        || windows.iter().any(|w| &w.name == col)
+        || keys.iter().any(|k| matches!(k, Expression::Column(key) if same_column(col, key))),
+        _ => false,
+    };
+    exprs.find_map(|e| e.column_outside(&known))
+    .or_else(|| having.and_then(|h| h.column_outside(&known)))
+}
+
+/// Rows of `table` left once `offset` of them are skipped, `limit` of them at most
+fn paginate(table: &Table<Record>, offset: usize, limit: Option<usize>) -> Result<Vec<Vec<Value>>, QueryError> {
+    Ok(table.select(&None, &Columns::All, &None)?
+        .into_iter()
+        .skip(offset)
+        .take(limit.unwrap_or(usize::MAX))
+        .collect())
+}
+
+/// Generic SQL, along with the `REGEXP` and `RLIKE` operators of MySQL, parsed as `~`, and `NOT REGEXP`, parsed as `!~`
+#[derive(Debug)]
+struct SqlDialect;
+
+impl SqlDialect {
+    /// Precedence the parser gives to LIKE, shared by REGEXP
+    const REGEXP_PRECEDENCE: u8 = 19;
+
+    /// Whether the `n`th next token is the REGEXP or RLIKE operator
+    fn regexp_operator_at(parser: &Parser, n: usize) -> bool {
+        matches!(
+            parser.peek_nth_token(n).token,
+            Token::Word(w) if w.quote_style.is_none() && matches!(w.value.to_uppercase().as_str(), "REGEXP" | "RLIKE")
+        )
+    }
+
+    /// Whether the next tokens are `NOT REGEXP` or `NOT RLIKE`
+    fn negated_regexp_operator(parser: &Parser) -> bool {
+        matches!(parser.peek_token().token, Token::Word(w) if w.keyword == Keyword::NOT) && Self::regexp_operator_at(parser, 1)
+    }
+}
+
+impl Dialect for SqlDialect {
+    fn is_identifier_start(&self, ch: char) -> bool {
+        GenericDialect.is_identifier_start(ch)
+    }
+
+    fn is_identifier_part(&self, ch: char) -> bool {
+        GenericDialect.is_identifier_part(ch)
+    }
+
+    fn supports_group_by_expr(&self) -> bool {
+        GenericDialect.supports_group_by_expr()
+    }
+
+    // The operators are only looked for after an operand, so that columns may be named `regexp` or `rlike`
+    fn get_next_precedence(&self, parser: &Parser) -> Option<Result<u8, ParserError>> {
+        (Self::regexp_operator_at(parser, 0) || Self::negated_regexp_operator(parser)).then_some(Ok(Self::REGEXP_PRECEDENCE))
+    }
+
+    fn parse_infix(&self, parser: &mut Parser, expr: &Expr, _precedence: u8) -> Option<Result<Expr, ParserError>> {
+        let op = if Self::regexp_operator_at(parser, 0) {
+            BinaryOperator::PGRegexMatch
+        } else if Self::negated_regexp_operator(parser) {
+            parser.next_token();
+            BinaryOperator::PGRegexNotMatch
+        } else {
+            return None;
+        };
+        parser.next_token();
+        Some(parser.parse_subexpr(Self::REGEXP_PRECEDENCE).map(|right| Expr::BinaryOp {
+            left: Box::new(expr.clone()),
+            op,
+            right: Box::new(right),
+        }))
+    }
+}
+
+/// Highest amount of iterations of a recursive common table, over which its query is considered endless
+const MAX_RECURSION: usize = 1000;
+
+/// Whether `command` reads the table `name` in its FROM clause
+fn reads_table(command: &Command, name: &str) -> bool {
+    match command {
+        Command::Select { tables, .. } => tables.iter()
+        .any(|t| t.table.name == name || t.joins.iter().any(|j| j.table.name == name)),
+        Command::SetOperation { left, right, .. } => reads_table(left, name) || reads_table(right, name),
+        Command::With { tables, query, .. } => tables.iter().all(|t| t.name != name) && reads_table(query, name),
+        _ => false,
+    }
+}
+
+/// What a query may refer to besides the stored tables
+#[derive(Clone, Copy, Default)]
+struct Scope<'a> {
+    /// Records of the enclosing statements, the nearest first
+    records: &'a [&'a Record],
+    /// Common tables defined by the enclosing WITH clauses, the nearest last
+    tables: &'a [&'a Table<Record>],
+}
+
+/// Headers of a table read by a query, `None` when they are only known once it is run
+type Headers = Option<Vec<String>>;
+
+/// Columns of the enclosing statements read by a subquery, and the rows it returned for each of their values
+struct Correlation {
+    columns: Vec<String>,
+    /// Values are keyed along with their text, so that equal numbers of different types are told apart
+    rows: HashMap<Vec<(Value, String)>, Vec<Vec<Value>>>,
+}
+
+/// Binding of the subqueries of a statement, which may refer to the records of the enclosing statements
+struct Context<'a> {
+    session: &'a Session,
+    /// Table of the statement, whose columns are bound to the enclosing records when it does not hold them.
+    /// Without it, the subqueries are run for the record at the top of the scope
+    table: Option<&'a Table<Record>>,
+    scope: Scope<'a>,
+    /// Subqueries met so far for the statement and its records, by SQL
+    correlations: Rc<RefCell<HashMap<String, Correlation>>>,
+}
+
+impl<'a> Context<'a> {
+    fn new(session: &'a Session, table: Option<&'a Table<Record>>, scope: Scope<'a>) -> Self {
+        Self { session, table, scope, correlations: Rc::default() }
+    }
+
+    /// Whether `conditions` hold for `record`, their remaining subqueries being run for it
+    fn holds(&self, conditions: &Option<Condition>, record: &Record) -> Result<bool, ExecutionError> {
+        match conditions {
+            None => Ok(true),
+            Some(cond) if cond.has_subquery() => {
+                let records = [&[record], self.scope.records].concat();
+                Ok(record.satisfy_conditions(&cond.bound(&self.for_record(&records))?)?)
+            },
+            Some(cond) => Ok(record.satisfy_conditions(cond)?),
+        }
+    }
+
+    /// Value of `expr` for `record`, its remaining subqueries being run for it
+    fn evaluate(&self, expr: &Expression, record: &Record) -> Result<Value, ExecutionError> {
+        if !expr.has_subquery() {
+            return Ok(record.evaluate(expr)?);
+        }
+        let records = [&[record], self.scope.records].concat();
+        Ok(record.evaluate(&expr.bound(&self.for_record(&records))?)?)
+    }
+
+    /// Context running the subqueries for the first of `records`, sharing the rows they returned for the other records
+    fn for_record<'r>(&'r self, records: &'r [&'r Record]) -> Context<'r> {
+        Context {
+            session: self.session,
+            table: None,
+            scope: Scope { records, ..self.scope },
+            correlations: Rc::clone(&self.correlations),
+        }
+    }
+}
+
+impl Binding for Context<'_> {
+    fn column(&self, name: &str) -> Option<Value> {
+        match self.table?.find_column(name) {
+            Some(_) => None,
+            None => self.scope.records.iter().find_map(|r| r.get_attr_value(name).ok()),
+        }
+    }
+
+    fn rows(&self, subquery: &Subquery) -> Result<Option<Vec<Vec<Value>>>, ExecutionError> {
+        let mut correlations = self.correlations.borrow_mut();
+        let correlation = match correlations.entry(subquery.sql.clone()) {
+            Entry::Occupied(entry) => entry.into_mut(),
+            Entry::Vacant(entry) => {
+                let tables: Vec<(&str, Headers)> = self.scope.tables.iter().map(|t| (t.name(), Some(t.columns_names()))).collect();
+                let columns = self.session.outer_columns(&subquery.command, &tables)?;
+                entry.insert(Correlation { columns: columns.into_iter().map(String::from).collect(), rows: HashMap::new() })
+            },
+        };
+        // Subqueries reading the columns of the statement are run for each of its records, the other ones only once
+        if self.table.is_some_and(|table| correlation.columns.iter().any(|col| table.find_column(col).is_some())) {
+            return Ok(None);
+        }
+        // Records sharing the values read by the subquery share its rows
+        let key: Vec<(Value, String)> = correlation.columns.iter()
+        .map(|col| {
+            let value = self.scope.records.iter().find_map(|r| r.get_attr_value(col).ok()).unwrap_or(Value::Null);
+            let text = value.to_string();
+            (value, text)
+        })
+        .collect();
+        if let Some(rows) = correlation.rows.get(&key) {
+            return Ok(Some(rows.clone()));
+        }
+        let (_, rows) = self.session.query(&subquery.command, self.scope)?;
+        correlation.rows.insert(key, rows.clone());
+        Ok(Some(rows))
+    }
+}
+
+/// Runs SQL statements against the tables of a `DataStore`.
+/// Tables loaded from a directory are written back to their files after every statement modifying them,
+/// the ones of a session built from a `DataStore` only living in memory.
+pub struct Session {
+    store: DataStore,
+    directory: Option<Directory>,
+}
+
+impl Session {
+    pub fn new(store: DataStore) -> Self {
+        Self { store, directory: None }
+    }
+
+    pub fn from_directory(path: &str) -> Result<Self, ExecutionError> {
+        let mut directory = Directory::new(path.to_string())?;
+        directory.load_buffers()?;
+        let store = DataStore::from_directory(&directory)?;
+        Ok(Self { store, directory: Some(directory) })
+    }
+
+    pub fn store(&self) -> &DataStore {
+        &self.store
+    }
+
+    pub fn parse(sql: &str) -> Result<Vec<Statement>, ExecutionError> {
+        let tokens = Tokenizer::new(&GenericDialect, sql)
+        .tokenize()
+        .map_err(|e| ExecutionError::Parsing(e.to_string()))?;
+        Parser::new(&SqlDialect)
+        .with_tokens(tokens)
+        .parse_statements()
+        .map_err(|e| ExecutionError::Parsing(e.to_string()))
+    }
+
+    /// Parses `sql` and runs every statement in order, stopping at the first failing one.
+    pub fn execute(&mut self, sql: &str) -> Result<Vec<QueryResult>, ExecutionError> {
+        let mut results = Vec::new();
+        for st in Self::parse(sql)?.iter() {
+            results.push(self.run_statement(st)?);
+        }
+        Ok(results)
+    }
+
+    pub fn run_statement(&mut self, statement: &Statement) -> Result<QueryResult, ExecutionError> {
+        self.run_command(statement.deserialize_as_command()?)
+    }
+
+    pub fn run_command(&mut self, command: Command) -> Result<QueryResult, ExecutionError> {
+        match command {
+            Command::Select { .. } | Command::SetOperation { .. } | Command::With { .. } => {
+                let (headers, rows) = self.query(&command, Scope::default())?;
+                Ok(QueryResult::Rows { headers, rows })
+            },
+            Command::Update { table, updates, conditions } => {
+                let target = self.target(&table)?;
+                let context = Context::new(self, Some(&target), Scope::default());
+                let conditions = conditions?.map(|cond| cond.bound(&context)).transpose()?;
+                let updates = updates.iter()
+                .map(|(col, expr)| Ok((col.clone(), expr.bound(&context)?)))
+                .collect::<Result<Vec<(String, Expression)>, ExecutionError>>()?;
+                let new_values = target.iter()
+                .map(|r| {
+                    if !context.holds(&conditions, r)? {
+                        return Ok(None);
+                    }
+                    updates.iter()
+                    .map(|(col, expr)| Ok((col.clone(), context.evaluate(expr, r)?)))
+                    .collect::<Result<HashMap<String, Value>, ExecutionError>>()
+                    .map(Some)
+                })
+                .collect::<Result<Vec<Option<HashMap<String, Value>>>, ExecutionError>>()?;
+                let amount = self.store.update_records(&table, &new_values)?;
+                self.save(&table)?;
+                Ok(QueryResult::AffectedRows(amount))
+            },
+            Command::Insert { table, columns, rows } => {
+                // Values are computed as the columns of a SELECT without table, out of a record without columns
+                let empty = Record::new(vec![], Rc::default());
+                let context = Context::new(self, None, Scope::default());
+                let rows = rows.iter()
+                .map(|row| row.iter()
+                    .map(|expr| context.evaluate(expr, &empty))
+                    .collect::<Result<Vec<Value>, ExecutionError>>())
+                .collect::<Result<Vec<Vec<Value>>, ExecutionError>>()?;
+                self.insert_rows(&table, &columns, rows, "row of VALUES has")
+            },
+            Command::InsertSelect { table, columns, query } => {
+                // The query is run before any record is inserted, so that it never reads the ones it gives
+                let (_, rows) = self.query(&query, Scope::default())?;
+                self.insert_rows(&table, &columns, rows, "query returns")
+            },
+            Command::Delete { table, conditions } => {
+                let target = self.target(&table)?;
+                let context = Context::new(self, Some(&target), Scope::default());
+                let conditions = conditions?.map(|cond| cond.bound(&context)).transpose()?;
+                let matching = target.iter()
+                .map(|r| context.holds(&conditions, r))
+                .collect::<Result<Vec<bool>, ExecutionError>>()?;
+                let amount = self.store.delete_records(&table, &matching)?;
+                self.save(&table)?;
+                Ok(QueryResult::AffectedRows(amount))
+            },
+        }
+    }
+
+    /// Inserts `rows` into `table`, their values being given by position to `columns`, or to all the columns of the table.
+    /// The source of the rows is named by `source` in the error raised when their size does not match `columns`.
+    fn insert_rows(&mut self, table: &str, columns: &[String], rows: Vec<Vec<Value>>, source: &str) -> Result<QueryResult, ExecutionError> {
+        let elements = rows.into_iter()
+        .map(|row| match columns.len() {
+            0 => Ok(InsertElement::PlainValues(row)),
+            n if n == row.len() => Ok(InsertElement::MappedValues(columns.iter().cloned().zip(row).collect())),
+            n => Err(QueryError::new(&format!("{} columns listed, but the {} {} columns", n, source, row.len()))),
+        })
+        .collect::<Result<Vec<InsertElement>, QueryError>>()?;
+        let amount = self.store.insert_all(&Some(table.to_string()), elements)?;
+        self.save(table)?;
+        Ok(QueryResult::AffectedRows(amount))
+    }
+
+    /// Writes the records of `table` to its file, when the session has been loaded from a directory
+    fn save(&self, table: &str) -> Result<(), ExecutionError> {
+        if let (Some(directory), Some(t)) = (&self.directory, self.store.get_table(table)) {
+            directory.commit(table, t)?;
+        }
+        Ok(())
+    }
+
+    /// Records of the table modified by a statement, whose columns may also be qualified by the table name
+    fn target(&self, table: &str) -> Result<Table<Record>, QueryError> {
+        Ok(self.store.get_table(table)
+            .ok_or_else(|| QueryError::new(&format!("Unknown table {}", table)))?
+            .qualified(table))
+    }
+
+    /// Records of `table` for which `conditions` hold
+    fn filter(&self, table: &Table<Record>, conditions: Option<Condition>, scope: Scope) -> Result<Table<Record>, ExecutionError> {
+        let context = Context::new(self, Some(table), scope);
+        let conditions = conditions.map(|cond| cond.bound(&context)).transpose()?;
+        table.filter_by(|r| context.holds(&conditions, r))
+    }
+
+    /// Columns read by `command`, or by its subqueries, which none of the tables it reads holds, as the ones of the enclosing statements
+    /// read by a correlated subquery. `tables` gives the headers of the common tables it may read.
+    /// Columns of tables whose headers are not known, as well as the names of selected columns, are counted in.
+    fn outer_columns<'c>(&self, command: &'c Command, tables: &[(&str, Headers)]) -> Result<Vec<&'c str>, QueryError> {
+        match command {
+            Command::Select { tables: joined, projections, distinct, conditions, group_by, aggregates, having, windows, order_by, .. } => {
+                let read: Vec<(&str, Headers)> = joined.iter()
+                .flat_map(|t| std::iter::once(&t.table).chain(t.joins.iter().map(|j| &j.table)))
+                .map(|reference| Ok((reference.reference_name(), match tables.iter().rev().find(|(name, _)| *name == reference.name) {
+                    Some((_, headers)) => headers.clone(),
+                    None => Some(self.store.get_table(&reference.name)
+                        .ok_or_else(|| QueryError::unknown_table(&reference.name))?
+                        .columns_names()),
+                })))
+                .collect::<Result<Vec<(&str, Headers)>, QueryError>>()?;
+                // Aggregates and windows are computed as columns of the table of the statement
+                let holds = |col: &str| aggregates.iter().any(|a| a.name == col) || windows.iter().any(|w| w.name == col)
+                || read.iter().any(|(reference, headers)| {
+                    let name = match col.split_once('.') {
+                        Some((qualifier, name)) if qualifier == *reference => name,
+                        Some(_) => return false,
+                        None => col,
+                    };
+                    headers.as_ref().is_some_and(|h| h.iter().any(|header| header == name))
+                });
+                let mut found = vec![];
+                for p in projections.iter() {
+                    if let Projection::Expression(expr, _) = p {
+                        expr.references(&mut found);
+                    }
+                }
+                for j in joined.iter().flat_map(|t| t.joins.iter()) {
+                    if let JoinConstraint::On(cond) = &j.constraint {
+                        cond.references(&mut found);
+                    }
+                }
+                for cond in [conditions, having].into_iter().flat_map(|c| c.as_ref().ok()).flatten() {
+                    cond.references(&mut found);
+                }
+                let sort_keys = |order_by: &'c [OrderBy]| order_by.iter().filter_map(|o| match &o.key {
+                    SortKey::Expression(expr) => Some(expr),
+                    SortKey::Position(_) => None,
+                });
+                let window_exprs = windows.iter().flat_map(|w| {
+                    let function: Vec<&Expression> = match &w.function {
+                        WindowFunction::RowNumber | WindowFunction::Rank | WindowFunction::DenseRank => vec![],
+                        WindowFunction::Lag { expr, default, .. } | WindowFunction::Lead { expr, default, .. } => vec![expr, default],
+                        WindowFunction::FirstValue(expr) => vec![expr],
+                        WindowFunction::Aggregate(agg) => agg.argument.iter().collect(),
+                    };
+                    w.partition_by.iter().chain(sort_keys(&w.order_by)).chain(function)
+                });
+                group_by.iter()
+                .chain(aggregates.iter().filter_map(|a| a.argument.as_ref()))
+                .chain(window_exprs)
+                .chain(sort_keys(order_by))
+                .chain(match distinct {
+                    Some(Distinct::On(exprs)) => exprs.as_slice(),
+                    _ => &[],
+                })
+                .for_each(|expr| expr.references(&mut found));
+                let mut columns = vec![];
+                for reference in found.into_iter() {
+                    match reference {
+                        Reference::Column(col) => columns.push(col),
+                        Reference::Subquery(query) => columns.extend(self.outer_columns(&query.command, tables)?),
+                    }
+                }
+                columns.retain(|col| !holds(col));
+                Ok(columns)
+            },
+            Command::SetOperation { left, right, .. } => Ok([self.outer_columns(left, tables)?, self.outer_columns(right, tables)?].concat()),
+            Command::With { recursive, tables: defined, query } => {
+                let mut visible = tables.to_vec();
+                let mut columns = vec![];
+                for t in defined.iter() {
+                    let table = (t.name.as_str(), (!t.columns.is_empty()).then(|| t.columns.clone()));
+                    // A recursive common table reads itself
+                    if *recursive {
+                        visible.push(table);
+                        columns.extend(self.outer_columns(&t.query, &visible)?);
+                    } else {
+                        columns.extend(self.outer_columns(&t.query, &visible)?);
+                        visible.push(table);
+                    }
+                }
+                columns.extend(self.outer_columns(query, &visible)?);
+                Ok(columns)
+            },
+            _ => Ok(vec![]),
+        }
+    }
+
+    /// Headers and rows returned by a query
+    fn query(&self, command: &Command, scope: Scope) -> Result<(Vec<String>, Vec<Vec<Value>>), ExecutionError> {
+        match command {
+            Command::Select { .. } => self.select(command, scope),
+            Command::SetOperation { operator, all, left, right, order_by, limit, offset } => {
+                let (headers, rows) = self.query(left, scope)?;
+                let (other_headers, other_rows) = self.query(right, scope)?;
+                let context = Context::new(self, None, scope);
+                let mut combined = Table::from_rows("", headers, rows)
+                .combine(&Table::from_rows("", other_headers, other_rows), *operator, *all)?;
+                combined.sort_by(order_by, |r, expr| context.evaluate(expr, r))?;
+                Ok((combined.columns_names(), paginate(&combined, *offset, *limit)?))
+            },
+            Command::With { recursive, tables, query } => {
+                let mut defined: Vec<Table<Record>> = vec![];
+                for t in tables.iter() {
+                    // Each common table may read the ones defined before it
+                    let visible: Vec<&Table<Record>> = scope.tables.iter().copied().chain(defined.iter()).collect();
+                    let table = self.common_table(t, *recursive, Scope { tables: &visible, ..scope })?;
+                    defined.push(table);
+                }
+                let visible: Vec<&Table<Record>> = scope.tables.iter().copied().chain(defined.iter()).collect();
+                self.query(query, Scope { tables: &visible, ..scope })
+            },
+            _ => Err(ExecutionError::Querying(QueryError::new("Only queries return rows"))),
+        }
+    }
+
+    /// Rows of a common table of a WITH clause.
+    /// In a recursive one, the second query of a UNION reading the table is run again on the rows it has just given, until it gives no new row.
+    fn common_table(&self, table: &CommonTable, recursive: bool, scope: Scope) -> Result<Table<Record>, ExecutionError> {
+        let named = |headers: Vec<String>, rows: Vec<Vec<Value>>| -> Result<Table<Record>, ExecutionError> {
+            match (table.columns.len(), headers.len()) {
+                (0, _) => Ok(Table::from_rows(&table.name, headers, rows)),
+                (n, width) if n == width => Ok(Table::from_rows(&table.name, table.columns.clone(), rows)),
+                (n, width) => Err(ExecutionError::Querying(QueryError::new(&format!(
+                    "{} columns named for the common table {}, but its query returns {} columns", n, table.name, width,
+                )))),
+            }
+        };
+        let (all, anchor, step) = match &table.query {
+            Command::SetOperation { operator: SetOperator::Union, all, left, right, order_by, limit: None, offset: 0 }
+            if recursive && order_by.is_empty() && reads_table(right, &table.name) => (*all, left, right),
+            query => {
+                let (headers, rows) = self.query(query, scope)?;
+                return named(headers, rows);
+            },
+        };
+        let (headers, rows) = self.query(anchor, scope)?;
+        let mut result = named(headers, rows)?;
+        if !all {
+            result = result.distinct(None)?;
+        }
+        let mut working = result.clone();
+        for _ in 0..MAX_RECURSION {
+            if working.iter().next().is_none() {
+                return Ok(result);
+            }
+            let visible: Vec<&Table<Record>> = scope.tables.iter().copied().chain([&working]).collect();
+            let (headers, rows) = self.query(step, Scope { tables: &visible, ..scope })?;
+            if headers.len() != result.columns_names().len() {
+                return Err(ExecutionError::Querying(QueryError::new(&format!(
+                    "The queries of the common table {} return {} and {} columns", table.name, result.columns_names().len(), headers.len(),
+                ))));
+            }
+            // Rows given by an iteration are read by the next one under the names of the common table
+            let mut found = Table::from_rows(&table.name, result.columns_names(), rows);
+            if !all {
+                found = found.combine(&result, SetOperator::Except, false)?;
+            }
+            result = result.combine(&found, SetOperator::Union, true)?;
+            working = found;
+        }
+        Err(ExecutionError::Querying(QueryError::new(&format!(
+            "The common table {} is still growing after {} iterations", table.name, MAX_RECURSION,
+        ))))
+    }
+
+    /// Headers and rows returned by a SELECT
+    fn select(&self, command: &Command, scope: Scope) -> Result<(Vec<String>, Vec<Vec<Value>>), ExecutionError> {
+        let Command::Select {
+            tables,
+            projections,
+            distinct,
+            conditions,
+            group_by,
+            aggregates,
+            having,
+            windows,
+            order_by,
+            limit,
+            offset,
+        } = command else {
+            return Err(ExecutionError::Querying(QueryError::new("Not a SELECT")));
+        };
+        let mut source = self.filter(&self.store.join_with(tables, scope.tables)?, conditions.clone()?, scope)?;
+        // Wildcards stand for the columns of the joined tables, not for the aggregates and windows computed next
+        let selected = source.expand(projections)?;
+        // Without GROUP BY, aggregates and HAVING apply to a single group holding every record
+        let grouped = !group_by.is_empty() || !aggregates.is_empty() || !matches!(having, Ok(None));
+        let context = Context::new(self, Some(&source), scope);
+        let keys = group_by.iter()
+        .map(|key| match key {
+            // Positions and names of selected columns refer to their expressions, as in ORDER BY
+            Expression::Literal(Value::Integer(position)) => usize::try_from(*position).ok()
+            .and_then(|position| selected.get(position.checked_sub(1)?))
+            .ok_or_else(|| QueryError::new(&format!("No selected column at position {}", position)))?
+            .1
+            .bound(&context),
+            _ => output_expression(&source, &selected, key).bound(&context),
+        })
+        .collect::<Result<Vec<Expression>, ExecutionError>>()?;
+        if grouped {
+            source = source.group_by(&keys, aggregates, |r, expr| context.evaluate(expr, r))?;
+            source = self.filter(&source, having.clone()?, scope)?;
+        }
+        if !windows.is_empty() {
+            source = source.window(windows)?;
+        }
+        let context = Context::new(self, Some(&source), scope);
+        let outputs = selected.into_iter()
+        .map(|(name, expr)| Ok((name, expr.bound(&context)?)))
+        .collect::<Result<Vec<(String, Expression)>, ExecutionError>>()?;
+        let order_by = order_by.iter()
+        .map(|o| Ok(OrderBy {
+            key: SortKey::Expression(match &o.key {
+                // Positions refer to the selected columns rather than to the ones of the table
+                SortKey::Position(position) => outputs.get(position - 1)
+                .ok_or_else(|| QueryError::new(&format!("No selected column at position {}", position)))?
+                .1
+                .clone(),
+                SortKey::Expression(expr) => output_expression(&source, &outputs, expr).bound(&context)?,
+            }),
+            ascending: o.ascending,
+            nulls_first: o.nulls_first,
+        }))
+        .collect::<Result<Vec<OrderBy>, ExecutionError>>()?;
+        let distinct_keys = match distinct {
+            Some(Distinct::On(exprs)) => Some(exprs.iter()
+                .map(|expr| output_expression(&source, &outputs, expr).bound(&context))
+                .collect::<Result<Vec<Expression>, ExecutionError>>()?),
+            _ => None,
+        };
+        if grouped {
+            let ungrouped = ungrouped_column(&source, &keys, aggregates, windows, outputs.iter()
+                .map(|(_, expr)| expr)
+                .chain(order_by.iter().filter_map(|o| match &o.key {
+                    SortKey::Expression(expr) => Some(expr),
+                    SortKey::Position(_) => None,
+                }))
+                .chain(distinct_keys.iter().flatten()), having.as_ref().ok().and_then(|h| h.as_ref()));
+            if let Some(col) = ungrouped {
+                return Err(ExecutionError::Querying(QueryError::new(&format!(
+                    "The column {} must appear in GROUP BY or be used in an aggregate", col,
+                ))));
+            }
+        }
+        // Records are sorted and mapped once the table is left, the subqueries they run sharing their rows with the ones run so far
+        let context = Context { session: self, table: None, scope, correlations: Rc::clone(&context.correlations) };
+        source.sort_by(&order_by, |r, expr| context.evaluate(expr, r))?;
+        if let Some(keys) = distinct_keys {
+            source = source.distinct(Some(&keys))?;
+        }
+        let (headers, exprs): (Vec<String>, Vec<Expression>) = outputs.into_iter().unzip();
+        let mut projected = source.map(headers.clone(), |r| exprs.iter()
+            .map(|expr| context.evaluate(expr, r))
+            .collect())?;
+        if let Some(Distinct::Rows) = distinct {
+            projected = projected.distinct(None)?;
+        }
+        Ok((headers, paginate(&projected, *offset, *limit)?))
+    }
+}
@@ -1,18 +1,26 @@
 use crate::{
     errors::{QueryError, TableInitError, LoadingError, ExportError},
-    traits::{Queryable, Columns, Recordable, Condition, InsertElement, Data, Storage, Loadable}, file_parsing::Buffer,
+    traits::{
+        Queryable, Columns, Recordable, Condition, InsertElement, Data, Storage, Loadable, JoinKind, JoinConstraint,
+        JoinedTables, TableReference, Aggregate, AggregateFunction, OrderBy, SortKey, Expression, ArithmeticOperator, Projection,
+        SetOperator, Window, WindowFunction, FrameBound,
+    },
+    functions,
+    file_parsing::{Buffer, Dialect, DialectOptions},
+    schema::{ColumnType, TableSchema},
 };
-use crate::utils::Value;
-use std::{collections::{HashMap, BTreeMap}, rc::Rc, path::{Path, PathBuf}, fs::{DirEntry, File}, io::{Read, BufReader}};
+use crate::utils::{Value, DATE_FORMAT, TIMESTAMP_FORMAT};
+use std::{cmp::Ordering, collections::{HashMap, HashSet, BTreeMap}, rc::Rc, path::{Path, PathBuf}, fs::{DirEntry, File}, io::BufReader};
 
 use serde::{Serialize, Deserialize};
 
-#[derive(Debug, PartialEq, Eq)]
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub struct Table<T>
 where T: Recordable {
     name: String,
     columns_names: Rc<Vec<String>>,
     records: Vec<T>,
+    schema: Option<Rc<TableSchema>>,
 }
 
 pub struct TableIter<'a> {
@@ -22,69 +30,407 @@ pub struct TableIter<'a> {
 
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct Record {
-    values: Vec<Option<String>>,
+    /// Values shared by the copies of the record read under other headers, as when its columns are qualified
+    values: Rc<Vec<Value>>,
     headers: Rc<Vec<String>>,
 }
 
+/// Position of a column among `headers`.
+/// An unqualified name also matches a qualified header (`table.column`) as long as no other table holds the same column.
+fn find_column_index(headers: &[String], attr_name: &str) -> Result<usize, QueryError> {
+    if let Some(idx) = headers.iter().position(|n| n == attr_name) {
+        return Ok(idx);
+    }
+    if attr_name.contains('.') {
+        return Err(QueryError::unknown_column(attr_name));
+    }
+    let mut candidates = headers.iter()
+    .enumerate()
+    .filter(|(_, n)| n.rsplit_once('.').map(|(_, col)| col) == Some(attr_name));
+    match (candidates.next(), candidates.next()) {
+        (Some((idx, _)), None) => Ok(idx),
+        (Some(_), Some(_)) => Err(QueryError::new(&format!("Ambiguous column {}", attr_name))),
+        (None, _) => Err(QueryError::unknown_column(attr_name)),
+    }
+}
+
+fn duplicate_key(key: &[&Value]) -> QueryError {
+    QueryError::new(&format!(
+        "Duplicate primary key ({})",
+        key.iter().map(|v| v.to_string()).collect::<Vec<String>>().join(", "),
+    ))
+}
+
+/// One value of each type held by the column at `idx`, NULL aside
+fn column_samples(records: &[Record], idx: usize) -> Vec<&Value> {
+    let mut samples: Vec<&Value> = vec![];
+    for v in records.iter().map(|r| &r.values[idx]).filter(|v| !v.is_null()) {
+        if !samples.iter().any(|s| std::mem::discriminant(*s) == std::mem::discriminant(v)) {
+            samples.push(v);
+        }
+    }
+    samples
+}
+
+/// Column name without the table qualifying it
+fn unqualified(column: &str) -> &str {
+    match column.split_once('.') {
+        Some((qualifier, col)) if qualifier.chars().all(|c| c.is_alphanumeric() || c == '_') => col,
+        _ => column,
+    }
+}
+
+/// Value of the aggregate over `records`, its argument being computed for each record by `evaluate`
+fn aggregate<E: From<QueryError>>(
+    records: &[&Record],
+    agg: &Aggregate,
+    evaluate: &mut impl FnMut(&Record, &Expression) -> Result<Value, E>,
+) -> Result<Value, E> {
+    let values: Vec<Value> = match &agg.argument {
+        None => return Ok(Value::Integer(records.len() as i64)),
+        Some(arg) => records.iter()
+        .map(|r| evaluate(r, arg))
+        .filter(|v| !matches!(v, Ok(Value::Null)))
+        .collect::<Result<Vec<Value>, E>>()?,
+    };
+    Ok(aggregate_values(values, agg)?)
+}
+
+/// Value of the aggregate over the non-NULL `values` of its argument
+fn aggregate_values(mut values: Vec<Value>, agg: &Aggregate) -> Result<Value, QueryError> {
+    if agg.distinct {
+        let mut seen = HashSet::new();
+        values.retain(|v| seen.insert(v.clone()));
+    }
+    match agg.function {
+        AggregateFunction::Count => Ok(Value::Integer(values.len() as i64)),
+        AggregateFunction::Min => Ok(values.into_iter().min().unwrap_or(Value::Null)),
+        AggregateFunction::Max => Ok(values.into_iter().max().unwrap_or(Value::Null)),
+        AggregateFunction::Sum | AggregateFunction::Avg => {
+            if values.is_empty() {
+                return Ok(Value::Null);
+            }
+            let integers: Option<Vec<i64>> = values.iter()
+            .map(|v| match v {
+                Value::Integer(i) => Some(*i),
+                _ => None,
+            })
+            .collect();
+            if let (Some(integers), AggregateFunction::Sum) = (&integers, &agg.function) {
+                return integers.iter()
+                .try_fold(0i64, |acc, i| acc.checked_add(*i))
+                .map(Value::Integer)
+                .ok_or_else(|| QueryError::new(&format!("Integer overflow in {}", agg.name)));
+            }
+            let sum = values.iter()
+            .map(|v| match v {
+                Value::Integer(i) => Ok(*i as f64),
+                Value::Float(f) | Value::Decimal(f, _) => Ok(*f),
+                Value::Text(s) => s.trim().parse::<f64>().map_err(|_| QueryError::new(&format!("{} is not a number in {}", s, agg.name))),
+                v => Err(QueryError::new(&format!("{} is not a number in {}", v, agg.name))),
+            })
+            .sum::<Result<f64, QueryError>>()?;
+            match agg.function {
+                AggregateFunction::Avg => Ok(Value::Float(sum / values.len() as f64)),
+                _ => Ok(Value::Float(sum)),
+            }
+        },
+    }
+}
+
+/// Order of two records given their values of the `order_by` keys
+fn compare_keys(order_by: &[OrderBy], k1: &[Value], k2: &[Value]) -> Ordering {
+    for (o, (v1, v2)) in order_by.iter().zip(k1.iter().zip(k2.iter())) {
+        let ordering = match (v1, v2) {
+            (Value::Null, Value::Null) => Ordering::Equal,
+            (Value::Null, _) if o.nulls_first => Ordering::Less,
+            (Value::Null, _) => Ordering::Greater,
+            (_, Value::Null) if o.nulls_first => Ordering::Greater,
+            (_, Value::Null) => Ordering::Less,
+            (v1, v2) if o.ascending => v1.cmp(v2),
+            (v1, v2) => v2.cmp(v1),
+        };
+        if ordering != Ordering::Equal {
+            return ordering;
+        }
+    }
+    Ordering::Equal
+}
+
+/// Position in the sorted partition where a frame starts (or ends, past its last record),
+/// `groups` being the ranges of positions the bounds count
+fn frame_position(bound: FrameBound, group: usize, groups: &[(usize, usize)], end: bool) -> usize {
+    let len = groups.last().map(|(_, e)| *e).unwrap_or(0);
+    let position = |(start, stop): (usize, usize)| if end { stop } else { start };
+    match bound {
+        FrameBound::UnboundedPreceding => 0,
+        FrameBound::Preceding(n) if n > group => 0,
+        FrameBound::Preceding(n) => position(groups[group - n]),
+        FrameBound::CurrentRow => position(groups[group]),
+        FrameBound::Following(n) => groups.get(group + n).map(|g| position(*g)).unwrap_or(len),
+        FrameBound::UnboundedFollowing => len,
+    }
+}
+
+/// Values of the window function for each of the `records`, in the same order
+fn window_values(records: &[Record], window: &Window) -> Result<Vec<Value>, QueryError> {
+    let mut partitions: Vec<Vec<usize>> = Vec::new();
+    let mut partitions_indexes: HashMap<Vec<Value>, usize> = HashMap::new();
+    for (i, r) in records.iter().enumerate() {
+        let key = window.partition_by.iter()
+        .map(|expr| r.evaluate(expr))
+        .collect::<Result<Vec<Value>, QueryError>>()?;
+        match partitions_indexes.get(&key) {
+            Some(idx) => partitions[*idx].push(i),
+            None => {
+                partitions_indexes.insert(key, partitions.len());
+                partitions.push(vec![i]);
+            },
+        }
+    }
+    let mut res = vec![Value::Null; records.len()];
+    for partition in partitions.iter() {
+        let keys = partition.iter()
+        .map(|i| window.order_by.iter()
+            .map(|o| match &o.key {
+                SortKey::Expression(expr) => records[*i].evaluate(expr),
+                // Positions only refer to selected columns, which do not exist yet
+                SortKey::Position(position) => Err(QueryError::new(&format!("Position {} cannot sort the records of a window", position))),
+            })
+            .collect::<Result<Vec<Value>, QueryError>>())
+        .collect::<Result<Vec<Vec<Value>>, QueryError>>()?;
+        let mut sorted: Vec<usize> = (0..partition.len()).collect();
+        sorted.sort_by(|a, b| compare_keys(&window.order_by, &keys[*a], &keys[*b]));
+        let rows: Vec<&Record> = sorted.iter().map(|j| &records[partition[*j]]).collect();
+        // Ranges of positions of the records sorted as equal, and the range each record belongs to
+        let mut peers: Vec<(usize, usize)> = Vec::new();
+        let mut peer_group = Vec::with_capacity(rows.len());
+        for pos in 0..sorted.len() {
+            match peers.last_mut() {
+                Some((_, end)) if compare_keys(&window.order_by, &keys[sorted[pos - 1]], &keys[sorted[pos]]) == Ordering::Equal => {
+                    *end = pos + 1;
+                },
+                _ => peers.push((pos, pos + 1)),
+            }
+            peer_group.push(peers.len() - 1);
+        }
+        let single_rows: Vec<(usize, usize)> = (0..rows.len()).map(|pos| (pos, pos + 1)).collect();
+        for (pos, r) in rows.iter().enumerate() {
+            let (groups, group) = if window.frame.rows { (&single_rows, pos) } else { (&peers, peer_group[pos]) };
+            let start = frame_position(window.frame.start, group, groups, false);
+            let end = frame_position(window.frame.end, group, groups, true).max(start);
+            let value = match &window.function {
+                WindowFunction::RowNumber => Value::Integer(pos as i64 + 1),
+                WindowFunction::Rank => Value::Integer(peers[peer_group[pos]].0 as i64 + 1),
+                WindowFunction::DenseRank => Value::Integer(peer_group[pos] as i64 + 1),
+                WindowFunction::Lag { expr, offset, default } => match pos.checked_sub(*offset) {
+                    Some(p) => rows[p].evaluate(expr)?,
+                    None => r.evaluate(default)?,
+                },
+                WindowFunction::Lead { expr, offset, default } => match rows.get(pos + offset) {
+                    Some(next) => next.evaluate(expr)?,
+                    None => r.evaluate(default)?,
+                },
+                WindowFunction::FirstValue(expr) if start < end => rows[start].evaluate(expr)?,
+                WindowFunction::FirstValue(_) => Value::Null,
+                WindowFunction::Aggregate(agg) => aggregate(&rows[start..end], agg, &mut |r, expr| r.evaluate(expr))?,
+            };
+            res[partition[sorted[pos]]] = value;
+        }
+    }
+    Ok(res)
+}
+
 impl Record {
-    pub fn new(values: Vec<Option<String>>, headers: Rc<Vec<String>>) -> Record {
-        Record { values, headers }
+    pub fn new(values: Vec<Value>, headers: Rc<Vec<String>>) -> Record {
+        Record { values: Rc::new(values), headers }
+    }
+
+    /// Same record read under `headers`, sharing its values
+    fn with_headers(&self, headers: &Rc<Vec<String>>) -> Record {
+        Record { values: Rc::clone(&self.values), headers: Rc::clone(headers) }
+    }
+
+    /// Truth value of `cond` for the record, `None` standing for SQL UNKNOWN, as when NULL is compared
+    fn truth_value(&self, cond: &Condition) -> Result<Option<bool>, QueryError> {
+        let compare = |left: &Expression, right: &Expression| -> Result<Option<Ordering>, QueryError> {
+            Ok(self.evaluate(left)?.compare(&self.evaluate(right)?))
+        };
+        match cond {
+            Condition::Compare(left, op, right) => Ok(compare(left, right)?.map(|o| op.holds(o))),
+            Condition::In(expr, values) => {
+                let mut res = Some(false);
+                for v in values.iter() {
+                    match compare(expr, v)? {
+                        Some(Ordering::Equal) => return Ok(Some(true)),
+                        Some(_) => (),
+                        None => res = None,
+                    }
+                }
+                Ok(res)
+            },
+            Condition::Between(expr, low, high) => Ok(and(
+                compare(expr, low)?.map(|o| o != Ordering::Less),
+                compare(expr, high)?.map(|o| o != Ordering::Greater),
+            )),
+            Condition::IsNull(expr) => Ok(Some(self.evaluate(expr)?.is_null())),
+            Condition::Like { expr, pattern, escape, case_insensitive } => match self.evaluate(expr)? {
+                Value::Null => Ok(None),
+                v if *case_insensitive => Ok(Some(like(&v.to_string().to_lowercase(), &pattern.to_lowercase(), *escape))),
+                v => Ok(Some(like(&v.to_string(), pattern, *escape))),
+            },
+            Condition::Regex(expr, re) => match self.evaluate(expr)? {
+                Value::Null => Ok(None),
+                v => Ok(Some(re.is_match(&v.to_string()))),
+            },
+            Condition::Not(cnd) => Ok(self.truth_value(cnd)?.map(|b| !b)),
+            Condition::Or(cnd1, cnd2) => Ok(or(self.truth_value(cnd1)?, self.truth_value(cnd2)?)),
+            Condition::And(cnd1, cnd2) => Ok(and(self.truth_value(cnd1)?, self.truth_value(cnd2)?)),
+            Condition::Truth(expr) => match self.evaluate(expr)? {
+                Value::Boolean(b) => Ok(Some(b)),
+                Value::Null => Ok(None),
+                v => Err(QueryError::new(&format!("{} is not a boolean", v))),
+            },
+            // Subqueries are run by the session, which binds their results before records are checked
+            Condition::InSubquery(_, query) | Condition::Exists(query) => Err(QueryError::new(
+                &format!("Subquery run outside of a session : {}", query.sql),
+            )),
+        }
+    }
+}
+
+/// Whether `text` matches the LIKE `pattern`, in which `escape` makes the following wildcard literal
+fn like(text: &str, pattern: &str, escape: Option<char>) -> bool {
+    // Pattern items : `None` for `%`, `Some(None)` for `_` and `Some(Some(c))` for a literal character
+    let mut items: Vec<Option<Option<char>>> = Vec::new();
+    let mut chars = pattern.chars();
+    while let Some(c) = chars.next() {
+        match c {
+            e if Some(e) == escape => items.push(Some(Some(chars.next().unwrap_or(e)))),
+            '%' => items.push(None),
+            '_' => items.push(Some(None)),
+            c => items.push(Some(Some(c))),
+        }
+    }
+    let text: Vec<char> = text.chars().collect();
+    let (mut t, mut p) = (0, 0);
+    // Positions to resume from when the characters following the last `%` do not match
+    let mut backtrack: Option<(usize, usize)> = None;
+    while t < text.len() {
+        match items.get(p) {
+            Some(None) => {
+                backtrack = Some((p + 1, t));
+                p += 1;
+            },
+            Some(Some(expected)) if expected.is_none_or(|c| c == text[t]) => {
+                t += 1;
+                p += 1;
+            },
+            _ => match backtrack {
+                Some((bp, bt)) => {
+                    backtrack = Some((bp, bt + 1));
+                    p = bp;
+                    t = bt + 1;
+                },
+                None => return false,
+            },
+        }
+    }
+    items[p..].iter().all(|i| i.is_none())
+}
+
+/// Three-valued AND, `None` standing for UNKNOWN
+fn and(left: Option<bool>, right: Option<bool>) -> Option<bool> {
+    match (left, right) {
+        (Some(false), _) | (_, Some(false)) => Some(false),
+        (Some(true), Some(true)) => Some(true),
+        _ => None,
+    }
+}
+
+/// Three-valued OR, `None` standing for UNKNOWN
+fn or(left: Option<bool>, right: Option<bool>) -> Option<bool> {
+    match (left, right) {
+        (Some(true), _) | (_, Some(true)) => Some(true),
+        (Some(false), Some(false)) => Some(false),
+        _ => None,
     }
 }
 
 impl Recordable for Record {
-    fn get_record_as_collection(&self) -> Vec<Option<String>> {
-        self.values.clone()
+    fn get_record_as_collection(&self) -> Vec<Value> {
+        self.values.to_vec()
     }
 
-    fn get_attr_index_from_name(&self, attr_name: &String) -> Result<usize, QueryError> {
-        match self.headers.iter().position(|n| n == attr_name) {
-            Some(idx) => Ok(idx),
-            None => Err(QueryError),
-        }
+    fn get_attr_index_from_name(&self, attr_name: &str) -> Result<usize, QueryError> {
+        find_column_index(&self.headers, attr_name)
     }
 
-    fn update_values(&mut self, new_values: &HashMap<String, Option<String>>) -> Result<(), QueryError> {
-        let mut header_iter = self.headers.iter();
+    fn update_values(&mut self, new_values: &HashMap<String, Value>) -> Result<(), QueryError> {
         for (attr, val) in new_values {
-            match header_iter.position(|n| n == attr) {
-                Some(idx) => if let Some(v) = self.values.get_mut(idx) {
-                        *v = val.clone();
-                    } else {
-                        return Err(QueryError)
-                    },
-                None => return Err(QueryError),
+            let idx = self.get_attr_index_from_name(attr)?;
+            match Rc::make_mut(&mut self.values).get_mut(idx) {
+                Some(v) => *v = val.clone(),
+                None => return Err(QueryError::unknown_column(attr)),
             }
         }
         Ok(())
     }
 
     fn satisfy_conditions(&self, cond: &Condition) -> Result<bool, QueryError> {
-        match cond {
-            Condition::Equal(col, v) => Ok(self.get_attr_value(col)? == Some(v.to_string())),
-            Condition::GreaterThan(col, v) => Ok(self.get_attr_value(col)? > Some(v.to_string())),
-            Condition::LessThan(col, v) => Ok(self.get_attr_value(col)? < Some(v.to_string())),
-            Condition::Or(cnd1, cnd2) => Ok(self.satisfy_conditions(cnd1)? || self.satisfy_conditions(cnd2)?),
-            Condition::And(cnd1, cnd2) => Ok(self.satisfy_conditions(cnd1)? && self.satisfy_conditions(cnd2)?),
-        }
+        Ok(self.truth_value(cond)? == Some(true))
     }
 
-    fn get_attr_value(&self, attr_name: &String) -> Result<Option<String>, QueryError> {
+    fn get_attr_value(&self, attr_name: &str) -> Result<Value, QueryError> {
         let attr_index = self.get_attr_index_from_name(attr_name)?;
-         self.values.get(attr_index)
-        .map(|v| v.clone())
-        .ok_or(QueryError)
+        self.values.get(attr_index)
+        .cloned()
+        .ok_or_else(|| QueryError::unknown_column(attr_name))
     }
 
-    fn get_attr_values(&self, attr_names: &Vec<String>) -> Result<Vec<Option<String>>, QueryError> {
-        let attr_indexes = attr_names.iter()
-        .map(|name| self.get_attr_index_from_name(name))
-        .collect::<Result<Vec<usize>, QueryError>>()?;
-        Ok(self.values.iter()
-        .enumerate()
-        .filter(|(idx, _)| attr_indexes.contains(idx))
-        .map(|(_, v)| v.clone())
-        .collect())
+    fn get_attr_values(&self, attr_names: &[String]) -> Result<Vec<Value>, QueryError> {
+        attr_names.iter()
+        .map(|name| self.get_attr_value(name))
+        .collect()
+    }
+
+    fn evaluate(&self, expr: &Expression) -> Result<Value, QueryError> {
+        match expr {
+            Expression::Column(col) => self.get_attr_value(col),
+            Expression::Literal(v) => Ok(v.clone()),
+            Expression::BinaryOp { left, op, right } => functions::arithmetic(&self.evaluate(left)?, op, &self.evaluate(right)?),
+            Expression::Negate(e) => match self.evaluate(e)? {
+                Value::Interval(i) => Ok(Value::Interval(i.negated())),
+                v => functions::arithmetic(&Value::Integer(0), &ArithmeticOperator::Subtract, &v),
+            },
+            Expression::Function { name, args } => functions::call(
+                name,
+                &args.iter()
+                .map(|arg| self.evaluate(arg))
+                .collect::<Result<Vec<Value>, QueryError>>()?,
+            ),
+            Expression::Cast(e, ColumnType::Integer) => match self.evaluate(e)? {
+                Value::Float(f) | Value::Decimal(f, _) => Ok(Value::Integer(f.round() as i64)),
+                v => ColumnType::Integer.coerce(&v, DATE_FORMAT, TIMESTAMP_FORMAT).ok_or_else(|| QueryError::new(&format!("Cannot cast {} to {}", v, ColumnType::Integer))),
+            },
+            Expression::Cast(e, to) => {
+                let v = self.evaluate(e)?;
+                to.coerce(&v, DATE_FORMAT, TIMESTAMP_FORMAT).ok_or_else(|| QueryError::new(&format!("Cannot cast {} to {}", v, to)))
+            },
+            Expression::Condition(cond) => Ok(self.truth_value(cond)?.map_or(Value::Null, Value::Boolean)),
+            Expression::Case { branches, otherwise } => {
+                for (cond, result) in branches.iter() {
+                    if self.satisfy_conditions(cond)? {
+                        return self.evaluate(result);
+                    }
+                }
+                match otherwise {
+                    Some(e) => self.evaluate(e),
+                    None => Ok(Value::Null),
+                }
+            },
+            Expression::Subquery(query) => Err(QueryError::new(&format!("Subquery run outside of a session : {}", query.sql))),
+        }
     }
 }
 
@@ -104,7 +450,7 @@ impl Table<Record> {
         columns_names: &Vec<&str>,
     ) -> Result<Self, TableInitError> {
         // Checks columns_names has at least one column name
-        if columns_names.len() == 0 {
+        if columns_names.is_empty() {
             return Err(TableInitError::new(
                 "There should be at least one column name",
             ));
@@ -127,29 +473,440 @@ impl Table<Record> {
                 .map(|col| col.to_string())
                 .collect()),
             records: vec![],
+            schema: None,
         })
     }
 
-    pub fn iter(&self) -> TableIter {
+    /// Table whose records are checked against `schema` when inserted or updated
+    pub fn from_schema(name: &str, schema: TableSchema) -> Result<Self, TableInitError> {
+        schema.validate().map_err(|reason| TableInitError::new(&reason))?;
+        let mut t = Self::new(name, &schema.columns_names())?;
+        t.schema = Some(Rc::new(schema));
+        Ok(t)
+    }
+
+    /// Table holding rows computed by a query
+    pub fn from_rows(name: &str, columns_names: Vec<String>, rows: Vec<Vec<Value>>) -> Self {
+        let columns_names = Rc::new(columns_names);
+        Self {
+            name: name.to_string(),
+            records: rows.into_iter()
+            .map(|values| Record::new(values, Rc::clone(&columns_names)))
+            .collect(),
+            columns_names,
+            schema: None,
+        }
+    }
+
+    pub fn schema(&self) -> Option<&TableSchema> {
+        self.schema.as_deref()
+    }
+
+    /// Values of a record to be stored, once converted to the types of the schema
+    fn conform(&self, values: Vec<Value>) -> Result<Vec<Value>, QueryError> {
+        match &self.schema {
+            Some(schema) => schema.conform(&values).map_err(|reason| QueryError::new(&reason)),
+            None if values.len() == self.columns_names.len() => Ok(values),
+            None => Err(QueryError::new(&format!("Expected {} values, found {}.", self.columns_names.len(), values.len()))),
+        }
+    }
+
+    /// Checks no two records share the same primary key
+    fn check_primary_key<'a>(&self, records: impl IntoIterator<Item = &'a Record>) -> Result<(), QueryError> {
+        let indexes = match &self.schema {
+            Some(schema) if !schema.primary_key.is_empty() => schema.primary_key_indexes(),
+            _ => return Ok(()),
+        };
+        let mut keys = HashSet::new();
+        for r in records {
+            let key: Vec<&Value> = indexes.iter().map(|idx| &r.values[*idx]).collect();
+            if !keys.insert(key.clone()) {
+                return Err(duplicate_key(&key));
+            }
+        }
+        Ok(())
+    }
+
+    pub fn iter(&self) -> TableIter<'_> {
         TableIter { records: &self.records, current_record_index: 0 }
     }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn columns_names(&self) -> Vec<String> {
+        self.columns_names.to_vec()
+    }
+
+    /// Position of the column `name`, which may be left unqualified when no other table has such a column
+    pub fn find_column(&self, name: &str) -> Option<usize> {
+        find_column_index(&self.columns_names, name).ok()
+    }
+
+    /// Copy of the table keeping the records `predicate` holds for
+    pub fn filter_by<E>(&self, mut predicate: impl FnMut(&Record) -> Result<bool, E>) -> Result<Self, E> {
+        let mut records = Vec::new();
+        for r in self.records.iter() {
+            if predicate(r)? {
+                records.push(r.clone());
+            }
+        }
+        Ok(Self { name: self.name.clone(), columns_names: Rc::clone(&self.columns_names), records, schema: None })
+    }
+
+    /// One record per distinct value of the `group_by` keys, followed by one column per aggregate.
+    /// Other columns hold the values of the first record of each group.
+    /// The keys and the arguments of the aggregates are computed for each record by `evaluate`.
+    pub fn group_by<E: From<QueryError>>(
+        &self,
+        group_by: &[Expression],
+        aggregates: &[Aggregate],
+        mut evaluate: impl FnMut(&Record, &Expression) -> Result<Value, E>,
+    ) -> Result<Self, E> {
+        let mut groups: Vec<Vec<&Record>> = Vec::new();
+        let mut groups_indexes: HashMap<Vec<Value>, usize> = HashMap::new();
+        for r in self.records.iter() {
+            let key = group_by.iter()
+            .map(|expr| evaluate(r, expr))
+            .collect::<Result<Vec<Value>, E>>()?;
+            match groups_indexes.get(&key) {
+                Some(idx) => groups[*idx].push(r),
+                None => {
+                    groups_indexes.insert(key, groups.len());
+                    groups.push(vec![r]);
+                },
+            }
+        }
+        // Without GROUP BY, aggregates are computed over a single group, even an empty one
+        if group_by.is_empty() && groups.is_empty() {
+            groups.push(vec![]);
+        }
+        let columns_names: Rc<Vec<String>> = Rc::new(self.columns_names.iter()
+            .cloned()
+            .chain(aggregates.iter().map(|agg| agg.name.clone()))
+            .collect());
+        let mut records = Vec::new();
+        for g in groups.iter() {
+            let mut values = match g.first() {
+                Some(r) => r.values.to_vec(),
+                None => vec![Value::Null; self.columns_names.len()],
+            };
+            for agg in aggregates.iter() {
+                values.push(aggregate(g, agg, &mut evaluate)?);
+            }
+            records.push(Record::new(values, Rc::clone(&columns_names)));
+        }
+        Ok(Self { name: self.name.clone(), columns_names, records, schema: None })
+    }
+
+    /// Copy of the table followed by one column per window function, records keeping their order
+    pub fn window(&self, windows: &[Window]) -> Result<Self, QueryError> {
+        let mut values: Vec<Vec<Value>> = self.records.iter().map(|r| r.values.to_vec()).collect();
+        for w in windows.iter() {
+            for (record_values, v) in values.iter_mut().zip(window_values(&self.records, w)?) {
+                record_values.push(v);
+            }
+        }
+        let columns_names: Rc<Vec<String>> = Rc::new(self.columns_names.iter()
+            .cloned()
+            .chain(windows.iter().map(|w| w.name.clone()))
+            .collect());
+        let records = values.into_iter()
+        .map(|v| Record::new(v, Rc::clone(&columns_names)))
+        .collect();
+        Ok(Self { name: self.name.clone(), columns_names, records, schema: None })
+    }
+
+    /// Sorts the records, the value of each key expression being computed by `evaluate`
+    pub fn sort_by<E: From<QueryError>>(
+        &mut self,
+        order_by: &[OrderBy],
+        mut evaluate: impl FnMut(&Record, &Expression) -> Result<Value, E>,
+    ) -> Result<(), E> {
+        if order_by.is_empty() {
+            return Ok(());
+        }
+        // Sort keys are computed once per record, before any record is moved
+        let keys = self.records.iter()
+        .map(|r| order_by.iter()
+            .map(|o| match &o.key {
+                SortKey::Expression(expr) => evaluate(r, expr),
+                SortKey::Position(position) => Ok(r.values.get(position - 1)
+                    .cloned()
+                    .ok_or_else(|| QueryError::new(&format!("No column at position {}", position)))?),
+            })
+            .collect::<Result<Vec<Value>, E>>())
+        .collect::<Result<Vec<Vec<Value>>, E>>()?;
+        let mut keyed: Vec<(Vec<Value>, Record)> = keys.into_iter().zip(self.records.drain(..)).collect();
+        keyed.sort_by(|(k1, _), (k2, _)| compare_keys(order_by, k1, k2));
+        self.records = keyed.into_iter().map(|(_, r)| r).collect();
+        Ok(())
+    }
+
+    /// Output columns of the select list, wildcards being replaced by the columns they stand for
+    pub fn expand(&self, projections: &[Projection]) -> Result<Vec<(String, Expression)>, QueryError> {
+        let mut res = vec![];
+        for p in projections.iter() {
+            match p {
+                Projection::Wildcard(qualifier) => {
+                    let columns: Vec<(String, Expression)> = self.columns_names.iter()
+                    .filter(|col| match (qualifier, col.split_once('.')) {
+                        (None, _) => true,
+                        (Some(q), Some((table, _))) => table == q,
+                        (Some(_), None) => false,
+                    })
+                    .map(|col| (unqualified(col).to_string(), Expression::Column(col.clone())))
+                    .collect();
+                    if columns.is_empty() {
+                        return Err(QueryError::new(&format!("No column matching {}.*", qualifier.as_deref().unwrap_or(""))));
+                    }
+                    res.extend(columns);
+                },
+                Projection::Expression(expr, name) => res.push((name.clone(), expr.clone())),
+            }
+        }
+        Ok(res)
+    }
+
+    /// Copy of the table keeping only the first record of each value of `keys`, all the columns by default
+    pub fn distinct(&self, keys: Option<&[Expression]>) -> Result<Self, QueryError> {
+        let mut seen = HashSet::new();
+        let mut records = vec![];
+        for r in self.records.iter() {
+            let key = match keys {
+                Some(exprs) => exprs.iter()
+                .map(|expr| r.evaluate(expr))
+                .collect::<Result<Vec<Value>, QueryError>>()?,
+                None => r.values.to_vec(),
+            };
+            if seen.insert(key) {
+                records.push(r.clone());
+            }
+        }
+        Ok(Self { name: self.name.clone(), columns_names: Rc::clone(&self.columns_names), records, schema: None })
+    }
+
+    /// Table of the values `values` computes for each record, held by the columns `columns_names`
+    pub fn map<E>(&self, columns_names: Vec<String>, mut values: impl FnMut(&Record) -> Result<Vec<Value>, E>) -> Result<Self, E> {
+        let columns_names = Rc::new(columns_names);
+        let records = self.records.iter()
+        .map(|r| Ok(Record::new(values(r)?, Rc::clone(&columns_names))))
+        .collect::<Result<Vec<Record>, E>>()?;
+        Ok(Self { name: self.name.clone(), columns_names, records, schema: None })
+    }
+
+    /// Updates each record with the values at its position in `new_values`, `None` leaving it as is
+    pub fn update_records(&mut self, new_values: &[Option<HashMap<String, Value>>]) -> Result<usize, QueryError> {
+        if new_values.len() != self.records.len() {
+            return Err(QueryError::new(&format!("Expected values for {} records, found {}", self.records.len(), new_values.len())));
+        }
+        let mut updated_amount = 0;
+        // Records are only replaced once every updated one is valid
+        let mut records = self.records.clone();
+        for (r, values) in records.iter_mut().zip(new_values.iter()) {
+            if let Some(values) = values {
+                r.update_values(values)?;
+                r.values = Rc::new(self.conform(r.values.to_vec())?);
+                updated_amount += 1;
+            }
+        }
+        self.check_primary_key(&records)?;
+        self.records = records;
+        Ok(updated_amount)
+    }
+
+    /// Deletes the records whose position is flagged in `matching`
+    pub fn delete_records(&mut self, matching: &[bool]) -> Result<usize, QueryError> {
+        if matching.len() != self.records.len() {
+            return Err(QueryError::new(&format!("Expected flags for {} records, found {}", self.records.len(), matching.len())));
+        }
+        let initial_amount = self.records.len();
+        let mut flags = matching.iter();
+        self.records.retain(|_| flags.next() == Some(&false));
+        Ok(initial_amount - self.records.len())
+    }
+
+    /// Records of both tables combined by `operator`, keeping the columns names of this one.
+    /// Both tables must have as many columns, holding values which can be compared to each other.
+    pub fn combine(&self, other: &Self, operator: SetOperator, all: bool) -> Result<Self, QueryError> {
+        if self.columns_names.len() != other.columns_names.len() {
+            return Err(QueryError::new(&format!(
+                "Combined queries return {} and {} columns",
+                self.columns_names.len(),
+                other.columns_names.len(),
+            )));
+        }
+        for idx in 0..self.columns_names.len() {
+            let left = column_samples(&self.records, idx);
+            let right = column_samples(&other.records, idx);
+            if left.iter().any(|l| right.iter().any(|r| l.compare(r).is_none())) {
+                return Err(QueryError::new(&format!("Combined queries return values of different types in column {}", self.columns_names[idx])));
+            }
+        }
+        // Occurrences of each row of the other table not matched yet
+        let mut others: HashMap<&[Value], usize> = HashMap::new();
+        for r in other.records.iter() {
+            *others.entry(&r.values[..]).or_default() += 1;
+        }
+        // Without ALL, a row matched once in the other table is matched for good
+        let distinct = if all { None } else { Some(self.distinct(None)?) };
+        let mut records = vec![];
+        for r in distinct.as_ref().unwrap_or(self).records.iter() {
+            let kept = match (operator, others.get_mut(&r.values[..])) {
+                (SetOperator::Union, _) => true,
+                (SetOperator::Intersect, Some(count)) | (SetOperator::Except, Some(count)) if *count > 0 => {
+                    *count -= 1;
+                    operator == SetOperator::Intersect
+                },
+                (SetOperator::Intersect, _) => false,
+                (SetOperator::Except, _) => true,
+            };
+            if kept {
+                records.push(r.with_headers(&self.columns_names));
+            }
+        }
+        if operator == SetOperator::Union {
+            records.extend(other.records.iter().map(|r| r.with_headers(&self.columns_names)));
+        }
+        let combined = Self { name: self.name.clone(), columns_names: Rc::clone(&self.columns_names), records, schema: None };
+        if all {
+            Ok(combined)
+        } else {
+            combined.distinct(None)
+        }
+    }
+
+    /// Same table whose columns are prefixed by `qualifier`, as in `qualifier.column`, its records sharing their values with this one
+    pub fn qualified(&self, qualifier: &str) -> Self {
+        let columns_names: Rc<Vec<String>> = Rc::new(self.columns_names.iter()
+            .map(|col| format!("{}.{}", qualifier, col))
+            .collect());
+        Self {
+            name: qualifier.to_string(),
+            records: self.records.iter()
+            .map(|r| r.with_headers(&columns_names))
+            .collect(),
+            columns_names,
+            schema: None,
+        }
+    }
+
+    pub fn join(&self, other: &Self, kind: &JoinKind, constraint: &JoinConstraint) -> Result<Self, QueryError> {
+        let left_width = self.columns_names.len();
+        let right_width = other.columns_names.len();
+        let headers: Rc<Vec<String>> = Rc::new([self.columns_names.to_vec(), other.columns_names.to_vec()].concat());
+        // Pairs of (left index, right index) of the columns merged by USING or NATURAL
+        let merged: Vec<(usize, usize)> = match constraint {
+            JoinConstraint::Using(cols) => cols.iter()
+            .map(|col| Ok((
+                find_column_index(&self.columns_names, col)?,
+                find_column_index(&other.columns_names, col)?,
+            )))
+            .collect::<Result<Vec<(usize, usize)>, QueryError>>()?,
+            JoinConstraint::Natural => self.columns_names.iter()
+            .enumerate()
+            .filter_map(|(left_idx, col)| {
+                let unqualified = col.rsplit('.').next()?;
+                let right_idx = find_column_index(&other.columns_names, unqualified).ok()?;
+                Some((left_idx, right_idx))
+            })
+            .collect(),
+            JoinConstraint::On(_) | JoinConstraint::None => vec![],
+        };
+        let mut rows: Vec<Vec<Value>> = Vec::new();
+        let mut right_matched = vec![false; other.records.len()];
+        for l in self.records.iter() {
+            let mut left_matched = false;
+            for (right_idx, r) in other.records.iter().enumerate() {
+                let record = Record::new([&l.values[..], &r.values[..]].concat(), Rc::clone(&headers));
+                let matching = match constraint {
+                    JoinConstraint::On(cond) => record.satisfy_conditions(cond)?,
+                    JoinConstraint::Using(_) | JoinConstraint::Natural => merged.iter()
+                    .all(|(li, ri)| l.values[*li].compare(&r.values[*ri]) == Some(Ordering::Equal)),
+                    JoinConstraint::None => true,
+                };
+                if matching {
+                    left_matched = true;
+                    right_matched[right_idx] = true;
+                    rows.push(Rc::unwrap_or_clone(record.values));
+                }
+            }
+            if !left_matched && matches!(kind, JoinKind::LeftOuter | JoinKind::FullOuter) {
+                rows.push([&l.values[..], &vec![Value::Null; right_width]].concat());
+            }
+        }
+        if matches!(kind, JoinKind::RightOuter | JoinKind::FullOuter) {
+            for (r, matched) in other.records.iter().zip(right_matched) {
+                if !matched {
+                    rows.push([&vec![Value::Null; left_width], &r.values[..]].concat());
+                }
+            }
+        }
+        if merged.is_empty() {
+            return Ok(Self {
+                name: self.name.clone(),
+                records: rows.into_iter()
+                .map(|values| Record::new(values, Rc::clone(&headers)))
+                .collect(),
+                columns_names: headers,
+                schema: None,
+            });
+        }
+        // Merged columns only appear once, holding the value of whichever side is not NULL
+        let dropped: Vec<usize> = merged.iter().map(|(_, ri)| left_width + ri).collect();
+        let kept_headers: Rc<Vec<String>> = Rc::new(headers.iter()
+            .enumerate()
+            .filter(|(idx, _)| !dropped.contains(idx))
+            .map(|(_, h)| h.clone())
+            .collect());
+        let records = rows.into_iter()
+        .map(|mut values| {
+            for (li, ri) in merged.iter() {
+                if values[*li].is_null() {
+                    values[*li] = values[left_width + ri].clone();
+                }
+            }
+            let kept = values.into_iter()
+            .enumerate()
+            .filter(|(idx, _)| !dropped.contains(idx))
+            .map(|(_, v)| v)
+            .collect();
+            Record::new(kept, Rc::clone(&kept_headers))
+        })
+        .collect();
+        Ok(Self { name: self.name.clone(), columns_names: kept_headers, records, schema: None })
+    }
 }
 
 impl Data for Table<Record> {
-    fn bulk_load_data(&mut self, data: &Vec<Vec<Value>>) -> Result<(), LoadingError> {
-        for r in data {
-            if let Err(_) = self.insert(&None, InsertElement::PlainValues(r.clone())) {
-                return Err(LoadingError::InvalidRecord(String::new()));
-            }
+    fn bulk_load_data(&mut self, data: &[Vec<Value>]) -> Result<(), LoadingError> {
+        let mut records = Vec::new();
+        for (idx, r) in data.iter().enumerate() {
+            let values = match &self.schema {
+                Some(schema) => schema.conform(r)
+                .map_err(|reason| LoadingError::SchemaMismatch(format!("Table {}, record n°{} : {}", self.name, idx + 1, reason)))?,
+                None if r.len() == self.columns_names.len() => r.clone(),
+                None => return Err(LoadingError::InvalidRecord(format!("Table {}, record n°{} : expected {} values, found {}.", self.name, idx + 1, self.columns_names.len(), r.len()))),
+            };
+            records.push(Record::new(values, Rc::clone(&self.columns_names)));
         }
+        records.splice(0..0, self.records.iter().cloned());
+        self.check_primary_key(&records)
+        .map_err(|_| LoadingError::SchemaMismatch(format!("Table {} holds several records with the same primary key.", self.name)))?;
+        self.records = records;
         Ok(())
     }
     
-    fn get_records_as_collection(&self) -> Vec<Vec<Option<String>>> {
+    fn get_records_as_collection(&self) -> Vec<Vec<Value>> {
         self.records.iter()
         .map(|r| r.get_record_as_collection())
         .collect()
     }
+
+    fn get_headers(&self) -> Vec<String> {
+        self.columns_names()
+    }
 }
 
 impl Queryable<Record> for Table<Record> {
@@ -158,14 +915,14 @@ impl Queryable<Record> for Table<Record> {
         _object_names: &Option<Vec<String>>,
         attributes_names: &Columns,
         conditions: &Option<Condition>,
-    ) -> Result<Vec<Vec<Option<String>>>, QueryError> {
+    ) -> Result<Vec<Vec<Value>>, QueryError> {
         match (attributes_names, conditions) {
             (Columns::All, None) => Ok(self.records
             .iter()
             .map(|r| r.get_record_as_collection())
             .collect()),
             (Columns::All, Some(conds)) => {
-                let mut res: Vec<Vec<Option<String>>> = Vec::new();
+                let mut res: Vec<Vec<Value>> = Vec::new();
                 for el in self.records.iter() {
                     if el.satisfy_conditions(conds)? {
                         res.push(el.get_record_as_collection());
@@ -174,18 +931,18 @@ impl Queryable<Record> for Table<Record> {
                 Ok(res)
             },
             (Columns::ColumnNames(cols), Some(conds)) => {
-                let mut res: Vec<Vec<Option<String>>> = Vec::new();
+                let mut res: Vec<Vec<Value>> = Vec::new();
                 for el in self.records.iter() {
                     if el.satisfy_conditions(conds)? {
-                        res.push(el.get_attr_values(&cols)?);
+                        res.push(el.get_attr_values(cols)?);
                     }
                 }
                 Ok(res)
             },
             (Columns::ColumnNames(cols), None) => {
-                let mut res: Vec<Vec<Option<String>>> = Vec::new();
+                let mut res: Vec<Vec<Value>> = Vec::new();
                 for el in self.records.iter() {
-                    res.push(el.get_attr_values(&cols)?);
+                    res.push(el.get_attr_values(cols)?);
                 }
                 Ok(res)
             },
@@ -193,7 +950,8 @@ impl Queryable<Record> for Table<Record> {
         }
     }
 
-    fn delete(&mut self, _object_name: &Option<String>, conditions: &Option<Condition>) -> Result<(), QueryError> {
+    fn delete(&mut self, _object_name: &Option<String>, conditions: &Option<Condition>) -> Result<usize, QueryError> {
+        let initial_amount = self.records.len();
         match conditions {
             None => self.records.clear(),
             Some(conds) => {
@@ -206,48 +964,65 @@ impl Queryable<Record> for Table<Record> {
                 self.records = records_left;
         },
         }
-        Ok(())
+        Ok(initial_amount - self.records.len())
     }
 
-    fn update(&mut self, _object_name: &Option<String>, new_values: HashMap<String, Option<String>>, conditions: &Option<Condition>) -> Result<(), QueryError> {
-        match conditions {
-            None => for r in self.records.iter_mut() {
-                r.update_values(&new_values)?;
-            },
-            Some(cond) => for r in self.records.iter_mut() {
-                if r.satisfy_conditions(cond)? {
-                    r.update_values(&new_values)?;
-                }
-            },
-        }
-        Ok(())
+    fn update(&mut self, _object_name: &Option<String>, new_values: HashMap<String, Value>, conditions: &Option<Condition>) -> Result<usize, QueryError> {
+        let new_values = self.records.iter()
+        .map(|r| Ok(match conditions {
+            None => true,
+            Some(cond) => r.satisfy_conditions(cond)?,
+        }.then(|| new_values.clone())))
+        .collect::<Result<Vec<Option<HashMap<String, Value>>>, QueryError>>()?;
+        self.update_records(&new_values)
     }
 
-    fn insert(&mut self, _object_name: &Option<String>, new_record: InsertElement) -> Result<(), QueryError> {
-        match new_record {
-            InsertElement::PlainValues(values) => if values.len() == self.columns_names.len() {
-                self.records.push(Record::new(values, Rc::clone(&self.columns_names)));
-                Ok(())
-            } else {
-                Err(QueryError)
-            },
-            InsertElement::MappedValues(mappings) => {
-                let new_values = self.columns_names.iter()
-                .map(|attr| match mappings.get(attr) {
-                    Some(v) => v.clone(),
-                    None => None,
-                }).collect();
-                self.records.push(Record::new(new_values, Rc::clone(&self.columns_names)));
-                Ok(())
-            },
+    fn insert_all(&mut self, _object_name: &Option<String>, new_records: Vec<InsertElement>) -> Result<usize, QueryError> {
+        let mut records: Vec<Record> = Vec::with_capacity(new_records.len());
+        for new_record in new_records.into_iter() {
+            let values = match new_record {
+                InsertElement::PlainValues(values) => values,
+                InsertElement::MappedValues(mappings) => {
+                    let mut mapped: Vec<Option<Value>> = vec![None; self.columns_names.len()];
+                    for (attr, v) in mappings.into_iter() {
+                        mapped[find_column_index(&self.columns_names, &attr)?] = Some(v);
+                    }
+                    let mut values = Vec::new();
+                    for (idx, v) in mapped.into_iter().enumerate() {
+                        values.push(match (v, &self.schema) {
+                            (Some(v), _) => v,
+                            (None, Some(schema)) => schema.columns[idx].default_value(&schema.date_format, &schema.timestamp_format).map_err(|reason| QueryError::new(&reason))?,
+                            (None, None) => Value::Null,
+                        });
+                    }
+                    values
+                },
+            };
+            records.push(Record::new(self.conform(values)?, Rc::clone(&self.columns_names)));
         }
+        // Records of the same batch may not share a primary key either
+        self.check_primary_key(self.records.iter().chain(records.iter()))?;
+        let amount = records.len();
+        self.records.extend(records);
+        Ok(amount)
     }
 }
 
 #[derive(Debug, Serialize, Deserialize)]
 pub struct Config {
     data_filepaths: Option<Vec<String>>,
-    aliases: HashMap<String, String>
+    #[serde(default)]
+    aliases: HashMap<String, String>,
+    #[serde(default)]
+    tables: HashMap<String, TableSchema>,
+    #[serde(default)]
+    files: HashMap<String, DialectOptions>,
+}
+
+impl Config {
+    pub fn table_schema(&self, name: &str) -> Option<&TableSchema> {
+        self.tables.get(name)
+    }
 }
 
 pub struct Directory {
@@ -269,6 +1044,10 @@ impl Directory {
         Ok(Self { name, path: p_obj, buffers: HashMap::new() })
     }
 
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
     pub fn list_files(&self) -> Result<Vec<DirEntry>, LoadingError> {
         let mut res = Vec::new();
         for entry in self.path.read_dir()
@@ -289,7 +1068,7 @@ impl Directory {
             let path = dir_e.path();
             let ext = path.extension()?;
             let ext_as_str = ext.to_str()?;
-            if ext_as_str == "csv" {
+            if ext_as_str == "csv" || ext_as_str == "tsv" {
                 Some(dir_e)
             } else {
                 None
@@ -299,22 +1078,79 @@ impl Directory {
         Ok(res)
     }
 
+    /// Buffers of the data files, read with the dialect `config.json` gives them
     pub fn load_buffers(&mut self) -> Result<(), LoadingError> {
+        let config = self.read_config()?;
         for e in self.list_data_files()? {
             let p = e.path();
-            let file_name = e.file_name()
-            .to_str()
+            let file_name = p.file_stem()
+            .and_then(|stem| stem.to_str())
             .ok_or(LoadingError::FailedFileLoading(std::io::ErrorKind::Other))?
-            .to_string()
-            .replace(".csv", "");
+            .to_string();
             let source_path = p.to_str()
             .ok_or(LoadingError::FailedFileLoading(std::io::ErrorKind::Other))?;
             let source_type = crate::traits::SourceType::LocalFile;
-            self.buffers.insert(file_name, Buffer::load_from_source(source_path, source_type)?);
+            let buf = Buffer::load_from_source(source_path, source_type)?;
+            let mut dialect = Dialect::default();
+            if p.extension().and_then(|ext| ext.to_str()) == Some("tsv") {
+                dialect.delimiter = '\t';
+            }
+            if let Some(options) = config.as_ref().and_then(|c| c.files.get(&file_name)) {
+                if options.sniff {
+                    dialect = buf.sniff()?;
+                }
+                dialect = options.apply(dialect);
+            }
+            dialect.validate()
+            .map_err(|reason| LoadingError::InvalidRecord(format!("Invalid dialect for {} : {}", file_name, reason)))?;
+            self.buffers.insert(file_name, buf.with_dialect(dialect));
         }
         Ok(())
     }
 
+    /// Tables of the directory, built from the schemas of `config.json` when it declares them
+    pub fn load_tables(&self) -> Result<Vec<Table<Record>>, LoadingError> {
+        let config = self.read_config()?;
+        if let Some(c) = &config {
+            if let Some(name) = c.tables.keys().find(|name| !self.buffers.contains_key(*name)) {
+                return Err(LoadingError::SchemaMismatch(format!("No data file found for the table {}.", name)));
+            }
+        }
+        let mut tables = Vec::new();
+        for (name, buf) in self.buffers.iter() {
+            let headers = buf.read_headers()?;
+            let mut t = match config.as_ref().and_then(|c| c.table_schema(name)) {
+                Some(schema) => {
+                    // Files without header get their column names from the schema
+                    let matching = if buf.has_header() {
+                        schema.columns_names() == headers
+                    } else {
+                        schema.columns.len() == headers.len()
+                    };
+                    if !matching {
+                        return Err(LoadingError::SchemaMismatch(format!(
+                            "The columns of the table {} are {}, whereas the schema declares {}.",
+                            name,
+                            headers.join(", "),
+                            schema.columns_names().join(", "),
+                        )));
+                    }
+                    let dialect = buf.dialect();
+                    Table::from_schema(name, schema.clone().with_formats(&dialect.date_format, &dialect.timestamp_format))
+                },
+                None => Table::new(name, &headers.iter().map(|h| h.as_str()).collect()),
+            }
+            .map_err(|e| LoadingError::SchemaMismatch(e.to_string()))?;
+            // The schema reads the fields as they are written, instead of the inferred values
+            match t.schema() {
+                Some(_) => t.bulk_load_data(&buf.bulk_texts()?)?,
+                None => t.bulk_load_data(&buf.bulk_data()?)?,
+            }
+            tables.push(t);
+        }
+        Ok(tables)
+    }
+
     fn get_config_file(&self) -> Result<DirEntry, LoadingError> {
         let files = self.list_files()?;
         for f in files {
@@ -325,30 +1161,37 @@ impl Directory {
         Err(LoadingError::FailedFileLoading(std::io::ErrorKind::NotFound))
     }
 
+    /// Configuration of the directory, if it has a `config.json` file
+    fn read_config(&self) -> Result<Option<Config>, LoadingError> {
+        match self.get_config_file() {
+            Ok(_) => Ok(Some(self.get_config()?)),
+            Err(_) => Ok(None),
+        }
+    }
+
     pub fn get_config(&self) -> Result<Config, LoadingError> {
         let conf_file = self.get_config_file()?;
         let f = File::open(conf_file.path())
         .map_err(|e| LoadingError::FailedFileLoading(e.kind()))?;
         let buf = BufReader::new(f);
         let config: Result<Config, serde_json::Error> = serde_json::from_reader(buf);
-        println!("config = {:?}", config);
-        config.map_err(|_| LoadingError::FailedFileLoading(std::io::ErrorKind::Other))
+        config.map_err(|e| LoadingError::InvalidRecord(format!("Invalid config.json : {}", e)))
     }
 }
 
 impl Storage for Directory {
-    fn bulk_data(&self, section_name: &String, columns_amount: usize) -> Result<Vec<Vec<Value>>, LoadingError> {
+    fn bulk_data(&self, section_name: &str) -> Result<Vec<Vec<Value>>, LoadingError> {
         let buf = self.buffers.get(section_name);
         match buf {
-            Some(b) => b.bulk_data(columns_amount),
+            Some(b) => b.bulk_data(),
             None => Err(LoadingError::SourceNotListed),
         }
     }
 
-    fn dump_data(&self, section_name: &String, data: Vec<Vec<Value>>) -> Result<(), ExportError> {
+    fn dump_data(&self, section_name: &str, headers: &[String], data: Vec<Vec<Value>>) -> Result<(), ExportError> {
         let buf = self.buffers.get(section_name);
         match buf {
-            Some(b) => b.dump_data(data),
+            Some(b) => b.dump_data(headers, data),
             None => Err(ExportError::ResourceNotFound),
         }
     }
@@ -366,6 +1209,62 @@ impl DataStore {
         }
         Self { tables: res }
     }
+
+    pub fn from_directory(directory: &Directory) -> Result<Self, LoadingError> {
+        Ok(Self::new(directory.load_tables()?))
+    }
+
+    pub fn get_table(&self, name: &str) -> Option<&Table<Record>> {
+        self.tables.get(name)
+    }
+
+    /// Table holding every combination of records of the listed tables, whose columns are qualified by table name or alias
+    pub fn join(&self, tables: &[JoinedTables]) -> Result<Table<Record>, QueryError> {
+        self.join_with(tables, &[])
+    }
+
+    /// Same as `join`, the tables of `temporary` hiding the stored ones of the same name, the last of them first
+    pub fn join_with(&self, tables: &[JoinedTables], temporary: &[&Table<Record>]) -> Result<Table<Record>, QueryError> {
+        let mut res: Option<Table<Record>> = None;
+        for joined in tables.iter() {
+            let mut t = self.qualified_table(&joined.table, temporary)?;
+            for j in joined.joins.iter() {
+                t = t.join(&self.qualified_table(&j.table, temporary)?, &j.kind, &j.constraint)?;
+            }
+            res = match res {
+                None => Some(t),
+                Some(previous) => Some(previous.join(&t, &JoinKind::Cross, &JoinConstraint::None)?),
+            };
+        }
+        // Without any table, as in `SELECT 1`, a single record without columns is read
+        Ok(res.unwrap_or(Table::from_rows("", vec![], vec![vec![]])))
+    }
+
+    fn qualified_table(&self, reference: &TableReference, temporary: &[&Table<Record>]) -> Result<Table<Record>, QueryError> {
+        Ok(temporary.iter()
+        .rev()
+        .find(|t| t.name == reference.name)
+        .copied()
+        .or(self.tables.get(&reference.name))
+        .ok_or_else(|| QueryError::unknown_table(&reference.name))?
+        .qualified(reference.reference_name()))
+    }
+
+    pub fn update_records(&mut self, table: &str, new_values: &[Option<HashMap<String, Value>>]) -> Result<usize, QueryError> {
+        self.table_mut(table)?.update_records(new_values)
+    }
+
+    pub fn delete_records(&mut self, table: &str, matching: &[bool]) -> Result<usize, QueryError> {
+        self.table_mut(table)?.delete_records(matching)
+    }
+
+    fn table_mut(&mut self, name: &str) -> Result<&mut Table<Record>, QueryError> {
+        self.tables.get_mut(name).ok_or_else(|| QueryError::unknown_table(name))
+    }
+
+    pub fn tables_names(&self) -> Vec<String> {
+        self.tables.keys().cloned().collect()
+    }
 }
 
 impl Queryable<Record> for DataStore {
@@ -375,37 +1274,35 @@ impl Queryable<Record> for DataStore {
         attributes_names: &Columns,
         conditions: &Option<Condition>,
     ) -> Result<Vec<Vec<Value>>, QueryError> {
-        // For now, SELECT on a single table is implemented
-        // Result will be generated from the first table listed by the user, by default
-        let t_name = _object_names.as_ref()
-        .ok_or(QueryError)?
-        .first()
-        .ok_or(QueryError)?
-        .clone();
-        let t = self.tables.get(&t_name).ok_or(QueryError)?;
-        t.select(_object_names, attributes_names, conditions)
-    }
-
-    fn delete(&mut self, _object_name: &Option<String>, conditions: &Option<Condition>) -> Result<(), QueryError> {
-        let t_name = _object_name.as_ref().ok_or(QueryError)?;
-        let t = self.tables.get_mut(t_name).ok_or(QueryError)?;
+        // Listed tables are cross joined, as in `SELECT ... FROM t1, t2`
+        let tables: Vec<JoinedTables> = _object_names.as_ref()
+        .ok_or_else(|| QueryError::new("No table given"))?
+        .iter()
+        .map(|name| JoinedTables { table: TableReference::new(name), joins: vec![] })
+        .collect();
+        self.join(&tables)?.select(&None, attributes_names, conditions)
+    }
+
+    fn delete(&mut self, _object_name: &Option<String>, conditions: &Option<Condition>) -> Result<usize, QueryError> {
+        let t_name = _object_name.as_ref().ok_or_else(|| QueryError::new("No table given"))?;
+        let t = self.table_mut(t_name)?;
         t.delete(_object_name, conditions)
     }
 
     fn update(
         &mut self,
         _object_name: &Option<String>,
-        new_values: HashMap<String, Option<String>>,
+        new_values: HashMap<String, Value>,
         conditions: &Option<Condition>,
-    ) -> Result<(), QueryError> {
-        let t_name = _object_name.as_ref().ok_or(QueryError)?;
-        let t = self.tables.get_mut(t_name).ok_or(QueryError)?;
+    ) -> Result<usize, QueryError> {
+        let t_name = _object_name.as_ref().ok_or_else(|| QueryError::new("No table given"))?;
+        let t = self.table_mut(t_name)?;
         t.update(_object_name, new_values, conditions)
     }
 
-    fn insert(&mut self, _object_name: &Option<String>, new_record: InsertElement) -> Result<(), QueryError> {
-        let t_name = _object_name.as_ref().ok_or(QueryError)?;
-        let t = self.tables.get_mut(t_name).ok_or(QueryError)?;
-        t.insert(_object_name, new_record)
+    fn insert_all(&mut self, _object_name: &Option<String>, new_records: Vec<InsertElement>) -> Result<usize, QueryError> {
+        let t_name = _object_name.as_ref().ok_or_else(|| QueryError::new("No table given"))?;
+        let t = self.table_mut(t_name)?;
+        t.insert_all(_object_name, new_records)
     }
 }
\ No newline at end of file
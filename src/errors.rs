@@ -53,13 +53,34 @@ impl Display for TableInitError {
 }
 
 #[derive(Debug, PartialEq)]
-pub struct QueryError;
+pub struct QueryError {
+    reason: String,
+}
+
+impl QueryError {
+    pub fn new(reason: &str) -> Self {
+        Self { reason: reason.to_string() }
+    }
+
+    /// Error of a query referring to a column its tables do not hold
+    pub fn unknown_column(name: &str) -> Self {
+        Self::new(&format!("Unknown column {}", name))
+    }
+
+    pub fn unknown_table(name: &str) -> Self {
+        Self::new(&format!("Unknown table {}", name))
+    }
+
+    pub fn reason(&self) -> &str {
+        &self.reason
+    }
+}
 
 impl Error for QueryError {}
 
 impl Display for QueryError {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-      write!(f, "Error while querying...")
+      write!(f, "Error while querying : {}", self.reason)
     }
 }
 
@@ -81,13 +102,18 @@ pub enum LoadingError {
     SourceNotImplemented,
     FailedFileLoading(ErrorKind),
     SourceNotListed,
+    SchemaMismatch(String),
 }
 
 impl Error for LoadingError {}
 
 impl Display for LoadingError {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-      write!(f, "Error while loading data from the physical item.")
+        match &self {
+            LoadingError::InvalidRecord(reason) if !reason.is_empty() => write!(f, "Invalid record : {}", reason),
+            LoadingError::SchemaMismatch(reason) => write!(f, "Data not matching its schema : {}", reason),
+            _ => write!(f, "Error while loading data from the physical item."),
+        }
     }
 }
 
@@ -108,7 +134,7 @@ impl Display for ExportError {
     }
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub enum SerializeError {
     NotImplemented(String),
     NotImplementable,
@@ -121,4 +147,51 @@ impl Display for SerializeError {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         write!(f, "Failed serializing user query, reason : {:?}", self)
     }
-}
\ No newline at end of file
+}
+
+#[derive(Debug)]
+pub enum ExecutionError {
+    Parsing(String),
+    Serializing(SerializeError),
+    Querying(QueryError),
+    Loading(LoadingError),
+    Committing(CommitError),
+}
+
+impl Error for ExecutionError {}
+
+impl Display for ExecutionError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match &self {
+            ExecutionError::Parsing(reason) => write!(f, "Failed parsing SQL, reason : {}", reason),
+            ExecutionError::Serializing(e) => write!(f, "{}", e),
+            ExecutionError::Querying(e) => write!(f, "{}", e),
+            ExecutionError::Loading(e) => write!(f, "{}", e),
+            ExecutionError::Committing(e) => write!(f, "{}", e),
+        }
+    }
+}
+
+impl From<SerializeError> for ExecutionError {
+    fn from(value: SerializeError) -> Self {
+        ExecutionError::Serializing(value)
+    }
+}
+
+impl From<CommitError> for ExecutionError {
+    fn from(value: CommitError) -> Self {
+        ExecutionError::Committing(value)
+    }
+}
+
+impl From<QueryError> for ExecutionError {
+    fn from(value: QueryError) -> Self {
+        ExecutionError::Querying(value)
+    }
+}
+
+impl From<LoadingError> for ExecutionError {
+    fn from(value: LoadingError) -> Self {
+        ExecutionError::Loading(value)
+    }
+}
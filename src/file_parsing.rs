@@ -1,52 +1,437 @@
-use std::{fs::{File, OpenOptions}, io::{BufReader, BufRead, Write, Error}};
+use std::{fmt::Write as _, fs::{self, File, OpenOptions}, io::{Write, Error}};
 
-use crate::{traits::{Loadable, SourceType}, errors::{LoadingError, ExportError}, entities::Record, utils::Value};
+use chrono::{NaiveDate, NaiveDateTime};
+use serde::{Serialize, Deserialize};
+
+use crate::{traits::{Loadable, SourceType}, errors::{LoadingError, ExportError}, entities::Record, utils::{Value, DATE_FORMAT, TIMESTAMP_FORMAT}};
+
+/// Delimiters tried when sniffing a file, in order of preference
+const SNIFFED_DELIMITERS: [char; 4] = [';', ',', '\t', '|'];
+/// Amount of lines inspected when sniffing a file
+const SNIFFED_LINES: usize = 20;
 
 pub enum Source {
     FilePath(String),
     HttpUri(String)
 }
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LineEnding {
+    Lf,
+    CrLf,
+}
+
+impl LineEnding {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            LineEnding::Lf => "\n",
+            LineEnding::CrLf => "\r\n",
+        }
+    }
+}
+
+/// How the records of a file are written.
+/// Without `escape`, quotes are escaped by doubling them as in RFC 4180. Otherwise the escape character
+/// makes the following character literal inside quoted fields.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Dialect {
+    pub delimiter: char,
+    pub quote: char,
+    pub escape: Option<char>,
+    pub has_header: bool,
+    pub null_token: String,
+    /// `strftime` formats in which dates and timestamps are written
+    pub date_format: String,
+    pub timestamp_format: String,
+}
+
+impl Default for Dialect {
+    fn default() -> Self {
+        Self {
+            delimiter: ';',
+            quote: '"',
+            escape: None,
+            has_header: true,
+            null_token: String::new(),
+            date_format: DATE_FORMAT.to_string(),
+            timestamp_format: TIMESTAMP_FORMAT.to_string(),
+        }
+    }
+}
+
+impl Dialect {
+    pub fn validate(&self) -> Result<(), String> {
+        let special = [self.delimiter, self.quote, '\n', '\r'];
+        for (idx, c) in special.iter().enumerate() {
+            if special[idx + 1..].contains(c) {
+                return Err(format!("The character {:?} has several meanings.", c));
+            }
+        }
+        // Formatting fails on unknown specifiers, or on times of the day in a date format
+        let mut sample = String::new();
+        if write!(sample, "{}", NaiveDate::default().format(&self.date_format)).is_err() {
+            return Err(format!("The date format {:?} is invalid.", self.date_format));
+        }
+        if write!(sample, "{}", NaiveDateTime::default().format(&self.timestamp_format)).is_err() {
+            return Err(format!("The timestamp format {:?} is invalid.", self.timestamp_format));
+        }
+        match self.escape {
+            Some(e) if e == self.delimiter || e == '\n' || e == '\r' => Err(format!("The character {:?} has several meanings.", e)),
+            _ => Ok(()),
+        }
+    }
+}
+
+/// Dialect options of a file in `config.json`.
+/// With `sniff`, options which are not given are guessed from the first lines of the file.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct DialectOptions {
+    #[serde(default)]
+    pub sniff: bool,
+    pub delimiter: Option<char>,
+    pub quote: Option<char>,
+    pub escape: Option<char>,
+    pub header: Option<bool>,
+    pub null: Option<String>,
+    pub date_format: Option<String>,
+    pub timestamp_format: Option<String>,
+}
+
+impl DialectOptions {
+    /// `base` dialect, whose settings are replaced by the given options
+    pub fn apply(&self, base: Dialect) -> Dialect {
+        Dialect {
+            delimiter: self.delimiter.unwrap_or(base.delimiter),
+            quote: self.quote.unwrap_or(base.quote),
+            escape: self.escape.or(base.escape),
+            has_header: self.header.unwrap_or(base.has_header),
+            null_token: self.null.clone().unwrap_or(base.null_token),
+            date_format: self.date_format.clone().unwrap_or(base.date_format),
+            timestamp_format: self.timestamp_format.clone().unwrap_or(base.timestamp_format),
+        }
+    }
+}
+
+/// Field as written in a CSV file
+#[derive(Debug, PartialEq)]
+struct RawField {
+    text: String,
+    quoted: bool,
+}
+
+impl RawField {
+    fn new() -> Self {
+        Self { text: String::new(), quoted: false }
+    }
+
+    /// Quoted fields are never NULL, so that texts looking like the null token survive a round trip
+    fn to_value(&self, dialect: &Dialect) -> Value {
+        match (self.quoted, self.text.as_str()) {
+            (false, text) if text == dialect.null_token => Value::Null,
+            (_, "") => Value::Text(String::new()),
+            (_, text) => Value::infer_with_formats(text, &dialect.date_format, &dialect.timestamp_format),
+        }
+    }
+
+    /// Field as it is written, without inferring its type
+    fn to_raw_value(&self, dialect: &Dialect) -> Value {
+        match (self.quoted, self.text.as_str()) {
+            (false, text) if text == dialect.null_token => Value::Null,
+            (_, text) => Value::Text(text.to_string()),
+        }
+    }
+}
+
+/// Records of a CSV content following RFC 4180, written with `dialect`.
+/// Lines may end with LF or CRLF, and quoted fields may hold delimiters, escaped quotes and line breaks.
+/// Empty lines are skipped.
+fn parse_records(content: &str, dialect: &Dialect) -> Result<Vec<Vec<RawField>>, LoadingError> {
+    let content = content.strip_prefix('\u{feff}').unwrap_or(content);
+    let mut records = Vec::new();
+    let mut record: Vec<RawField> = Vec::new();
+    let mut field = RawField::new();
+    let mut in_quotes = false;
+    let mut line = 1;
+    let mut chars = content.chars().peekable();
+    while let Some(c) = chars.next() {
+        if in_quotes {
+            match c {
+                e if Some(e) == dialect.escape => match chars.next() {
+                    Some(escaped) => field.text.push(escaped),
+                    None => return Err(LoadingError::InvalidRecord(format!(
+                        "Line {} : the file ends with an escape character.", line
+                    ))),
+                },
+                q if q == dialect.quote && chars.peek() == Some(&dialect.quote) => {
+                    chars.next();
+                    field.text.push(q);
+                },
+                q if q == dialect.quote => {
+                    in_quotes = false;
+                    match chars.peek() {
+                        None | Some('\n') | Some('\r') => (),
+                        Some(d) if *d == dialect.delimiter => (),
+                        Some(other) => return Err(LoadingError::InvalidRecord(format!(
+                            "Line {} : unexpected character '{}' after a closing quote.", line, other
+                        ))),
+                    }
+                },
+                '\n' => {
+                    line += 1;
+                    field.text.push(c);
+                },
+                _ => field.text.push(c),
+            }
+            continue;
+        }
+        match c {
+            q if q == dialect.quote && field.text.is_empty() && !field.quoted => {
+                field.quoted = true;
+                in_quotes = true;
+            },
+            d if d == dialect.delimiter => record.push(std::mem::replace(&mut field, RawField::new())),
+            '\r' if chars.peek() == Some(&'\n') => (),
+            '\n' => {
+                line += 1;
+                if !record.is_empty() || field.quoted || !field.text.is_empty() {
+                    record.push(std::mem::replace(&mut field, RawField::new()));
+                    records.push(std::mem::take(&mut record));
+                }
+            },
+            _ => field.text.push(c),
+        }
+    }
+    if in_quotes {
+        return Err(LoadingError::InvalidRecord(format!("Line {} : a quoted field is never closed.", line)));
+    }
+    if !record.is_empty() || field.quoted || !field.text.is_empty() {
+        record.push(field);
+        records.push(record);
+    }
+    Ok(records)
+}
+
+/// `text` as a CSV field, quoted when it could not be read back otherwise
+fn quote_field(text: &str, dialect: &Dialect) -> String {
+    let needs_quotes = text.is_empty()
+    || text == dialect.null_token
+    || text.contains([dialect.delimiter, dialect.quote, '\n', '\r'])
+    || text.starts_with('\u{feff}');
+    if !needs_quotes {
+        return text.to_string();
+    }
+    let mut res = String::from(dialect.quote);
+    for c in text.chars() {
+        match dialect.escape {
+            Some(e) if c == e || c == dialect.quote => res.push(e),
+            None if c == dialect.quote => res.push(c),
+            _ => (),
+        }
+        res.push(c);
+    }
+    res.push(dialect.quote);
+    res
+}
+
+/// Dialect guessed from the first lines of `content` : the delimiter splitting every line in the same
+/// amount of fields, the quote character opening fields, and whether the first line differs from the others.
+fn sniff_dialect(content: &str) -> Dialect {
+    let content = content.strip_prefix('\u{feff}').unwrap_or(content);
+    let lines: Vec<&str> = content.lines()
+    .filter(|l| !l.trim().is_empty())
+    .take(SNIFFED_LINES)
+    .collect();
+    let mut dialect = Dialect::default();
+    let opens_fields = |q: char| lines.iter().any(|l| SNIFFED_DELIMITERS.iter()
+        .any(|d| l.starts_with(q) || l.contains(&format!("{}{}", d, q))));
+    if !opens_fields('"') && opens_fields('\'') {
+        dialect.quote = '\'';
+    }
+    // Each delimiter is scored by the amount of fields it gives, as long as it gives the same amount on every line
+    let mut best: Option<(char, usize)> = None;
+    for d in SNIFFED_DELIMITERS {
+        let candidate = Dialect { delimiter: d, ..dialect.clone() };
+        let records = match parse_records(&lines.join("\n"), &candidate) {
+            Ok(r) => r,
+            Err(_) => continue,
+        };
+        let widths: Vec<usize> = records.iter().map(|r| r.len()).collect();
+        let consistent = widths.first()
+        .filter(|w| **w > 1 && widths.iter().all(|other| other == *w));
+        if let Some(w) = consistent {
+            if best.is_none_or(|(_, best_width)| *w > best_width) {
+                best = Some((d, *w));
+            }
+        }
+    }
+    if let Some((d, _)) = best {
+        dialect.delimiter = d;
+    }
+    // A header is assumed unless a first line value has the type of the values below it
+    if let Ok(records) = parse_records(&lines.join("\n"), &dialect) {
+        if let Some((first, others)) = records.split_first() {
+            let typed_below = |idx: usize| !others.is_empty() && others.iter().all(|r| r.get(idx)
+                .map(|f| !matches!(f.to_value(&dialect), Value::Text(_) | Value::Null))
+                .unwrap_or(false));
+            let first_typed = first.iter()
+            .enumerate()
+            .any(|(idx, f)| typed_below(idx) && !matches!(f.to_value(&dialect), Value::Text(_)));
+            dialect.has_header = !first_typed;
+        }
+    }
+    dialect
+}
+
 pub struct Buffer {
-    source: Source
+    source: Source,
+    dialect: Dialect,
+    line_ending: LineEnding,
+    trailing_newline: bool,
 }
 
 impl Buffer {
     pub fn new(source: Source) -> Self {
-        Self { source }
+        Self { source, dialect: Dialect::default(), line_ending: LineEnding::Lf, trailing_newline: true }
+    }
+
+    /// Same buffer, whose first line holds column names or data depending on `has_header`
+    pub fn with_header(mut self, has_header: bool) -> Self {
+        self.dialect.has_header = has_header;
+        self
+    }
+
+    pub fn with_dialect(mut self, dialect: Dialect) -> Self {
+        self.dialect = dialect;
+        self
+    }
+
+    /// Same buffer, writing records separated by `line_ending`.
+    /// The last record is followed by a line ending as well when `trailing_newline` is set.
+    pub fn with_line_ending(mut self, line_ending: LineEnding, trailing_newline: bool) -> Self {
+        self.line_ending = line_ending;
+        self.trailing_newline = trailing_newline;
+        self
+    }
+
+    /// Same buffer, writing records with the line ending of its file, and a final line break when the file has one.
+    /// The ending of the first line is the one of the file, as the other lines may hold line breaks inside quotes.
+    fn with_file_line_ending(self) -> Result<Self, LoadingError> {
+        let content = self.read_content()?;
+        if content.is_empty() {
+            return Ok(self);
+        }
+        let line_ending = match content.find('\n') {
+            Some(idx) if content[..idx].ends_with('\r') => LineEnding::CrLf,
+            _ => LineEnding::Lf,
+        };
+        let trailing_newline = content.ends_with('\n');
+        Ok(self.with_line_ending(line_ending, trailing_newline))
+    }
+
+    pub fn has_header(&self) -> bool {
+        self.dialect.has_header
+    }
+
+    pub fn dialect(&self) -> &Dialect {
+        &self.dialect
+    }
+
+    /// Records of the file as they are written, every field being a text but the NULL ones,
+    /// for the tables whose schema gives the types of the columns
+    pub fn bulk_texts(&self) -> Result<Vec<Vec<Value>>, LoadingError> {
+        self.data_records(RawField::to_raw_value)
+    }
+
+    /// Records following the header, their fields converted by `to_value`
+    fn data_records(&self, to_value: fn(&RawField, &Dialect) -> Value) -> Result<Vec<Vec<Value>>, LoadingError> {
+        let records = self.read_records()?;
+        let columns_amount = match records.first() {
+            Some(r) => r.len(),
+            None => return Ok(vec![]),
+        };
+        records.iter()
+        .enumerate()
+        .skip(usize::from(self.dialect.has_header))
+        .map(|(idx, r)| if r.len() == columns_amount {
+            Ok(r.iter().map(|f| to_value(f, &self.dialect)).collect())
+        } else {
+            Err(LoadingError::InvalidRecord(format!(
+                "Record n°{} has {} columns instead of {}.", idx + 1, r.len(), columns_amount
+            )))
+        })
+        .collect()
     }
 
-    fn open_read_write_file(path: &String, truncate: bool) -> Result<File, Error> {
+    /// Dialect guessed from the first lines of the file
+    pub fn sniff(&self) -> Result<Dialect, LoadingError> {
+        Ok(sniff_dialect(&self.read_content()?))
+    }
+
+    fn open_read_write_file(path: &str, truncate: bool) -> Result<File, Error> {
         OpenOptions::new()
         .read(true)
         .write(true)
         .truncate(truncate)
         .create(true)
-        .open(path.as_str())
+        .open(path)
+    }
+
+    fn read_content(&self) -> Result<String, LoadingError> {
+        match &self.source {
+            Source::FilePath(file) => {
+                // Makes sure the file exists, as it is created by a commit otherwise
+                Self::open_read_write_file(file, false)
+                .map_err(|e| LoadingError::FailedFileLoading(e.kind()))?;
+                fs::read_to_string(file)
+                .map_err(|e| match e.kind() {
+                    std::io::ErrorKind::InvalidData => LoadingError::InvalidUTF8Encoding(e.kind()),
+                    kind => LoadingError::FailedFileLoading(kind),
+                })
+            },
+            Source::HttpUri(_) => Err(LoadingError::SourceNotImplemented),
+        }
     }
 
-    fn record_to_string(record: &Vec<Option<String>>) -> String {
+    fn read_records(&self) -> Result<Vec<Vec<RawField>>, LoadingError> {
+        parse_records(&self.read_content()?, &self.dialect)
+        .map_err(|e| match (e, &self.source) {
+            (LoadingError::InvalidRecord(reason), Source::FilePath(file)) => LoadingError::InvalidRecord(format!("{}, {}", file, reason)),
+            (e, _) => e,
+        })
+    }
+
+    fn record_to_string(record: &[Value], dialect: &Dialect) -> String {
         let res: Vec<String> = record.iter()
         .map(|val| match val {
-            Some(v) => v.clone(),
-            None => String::new(),
+            Value::Null => dialect.null_token.clone(),
+            Value::Date(d) => quote_field(&d.format(&dialect.date_format).to_string(), dialect),
+            Value::Timestamp(ts) => quote_field(&ts.format(&dialect.timestamp_format).to_string(), dialect),
+            v => quote_field(&v.to_string(), dialect),
         })
         .collect();
-        res.join(";")
+        res.join(&dialect.delimiter.to_string())
+    }
+
+    fn join_lines(lines: Vec<String>, line_ending: LineEnding, trailing_newline: bool) -> String {
+        let mut res = lines.join(line_ending.as_str());
+        if trailing_newline && !lines.is_empty() {
+            res.push_str(line_ending.as_str());
+        }
+        res
     }
 }
 
 impl Loadable<Record> for Buffer {
-    fn line_to_vec(line_string: &mut String, columns_amount: usize) -> Result<Vec<Option<String>>, LoadingError> {
-        if line_string.ends_with(";") {
-            line_string.pop();
-        }
-        let l: Vec<Option<String>> = line_string
-        .split(";")
-        .map(|attr| match attr {
-            "" => None,
-        s => Some(s.to_string())
-        }).collect();
+    fn line_to_vec(line_string: &mut String, columns_amount: usize) -> Result<Vec<Value>, LoadingError> {
+        let dialect = Dialect::default();
+        let mut records = parse_records(line_string, &dialect)?;
+        let l: Vec<Value> = match (records.pop(), records.is_empty()) {
+            (Some(r), true) => r.iter().map(|f| f.to_value(&dialect)).collect(),
+            (None, _) => vec![Value::Null],
+            (Some(_), false) => return Err(LoadingError::InvalidRecord(
+                String::from("The line holds several records.")
+            )),
+        };
         if l.len() == columns_amount {
             Ok(l)
         } else {
@@ -56,61 +441,61 @@ impl Loadable<Record> for Buffer {
         }
     }
 
-    fn collection_to_string(collection: Vec<Vec<Option<String>>>) -> String {
-        let res: Vec<String> = collection.iter()
-        .map(Self::record_to_string)
+    fn collection_to_string(collection: Vec<Vec<Value>>) -> String {
+        let lines = collection.iter()
+        .map(|r| Self::record_to_string(r, &Dialect::default()))
         .collect();
-
-        res.join("\n")
+        Self::join_lines(lines, LineEnding::Lf, true)
     }
 
     fn load_from_source(source_path: &str, source_type: SourceType) -> Result<Self, LoadingError> {
         match source_type {
-            SourceType::LocalFile => Ok(Self {source: Source::FilePath(source_path.to_string()) }),
+            SourceType::LocalFile => Self::new(Source::FilePath(source_path.to_string())).with_file_line_ending(),
             SourceType::Http => Err(LoadingError::SourceNotImplemented),
         }
     }
 
-    fn bulk_data(&self, columns_amount: usize) -> Result<Vec<Vec<Option<String>>>, LoadingError> {
-        match &self.source {
-            Source::FilePath(file) => {
-                let f = Self::open_read_write_file(file, false);
-                match f {
-                    Ok(file) => {
-                        let reader = BufReader::new(file);
-                        let lines_iter = reader.lines();
-                        let mut records_as_vec: Vec<Vec<Option<String>>> = Vec::new();
-                        for l in lines_iter {
-                            match l {
-                                Ok(mut l) => records_as_vec.push(Self::line_to_vec(&mut l, columns_amount)?),
-                                Err(e) => return Err(LoadingError::FailedFileLoading(e.kind())),
-                            }
-                        }
-                        Ok(records_as_vec)
-                    },
-                    Err(e) => Err(LoadingError::FailedFileLoading(e.kind())),
-                }
-            },
-            Source::HttpUri(_) => Err(LoadingError::SourceNotImplemented),
+    /// Column names written on the first line, or `col1`, `col2`... when the buffer has no header
+    fn read_headers(&self) -> Result<Vec<String>, LoadingError> {
+        let records = self.read_records()?;
+        let first = match (records.first(), &self.source) {
+            (Some(r), _) => r,
+            (None, Source::FilePath(file)) if self.dialect.has_header => return Err(LoadingError::InvalidRecord(
+                format!("The file {} has no header line.", file)
+            )),
+            (None, Source::FilePath(file)) => return Err(LoadingError::InvalidRecord(
+                format!("The file {} is empty, its columns cannot be discovered.", file)
+            )),
+            (None, Source::HttpUri(_)) => return Err(LoadingError::SourceNotImplemented),
+        };
+        if self.dialect.has_header {
+            Ok(first.iter().map(|f| f.text.clone()).collect())
+        } else {
+            Ok((1..=first.len()).map(|idx| format!("col{}", idx)).collect())
         }
     }
 
-    fn dump_data(&self, data: Vec<Vec<Value>>) -> Result<(), crate::errors::ExportError> {
+    fn bulk_data(&self) -> Result<Vec<Vec<Value>>, LoadingError> {
+        self.data_records(RawField::to_value)
+    }
+
+    fn dump_data(&self, headers: &[String], data: Vec<Vec<Value>>) -> Result<(), crate::errors::ExportError> {
         match &self.source {
-            Source::FilePath(p) => match Self::open_read_write_file(p, true) {
-                Ok(mut f) => {
-                    let col = Self::collection_to_string(data);
-                    println!("{:?}", col);
-                    let res: Result<(), Error> = write!(&mut f, "{}", col);
-                    match res {
-                        Ok(_) => Ok(()),
-                        Err(_) => Err(ExportError::Interrupted),
-                    }
-                },
-                Err(_) => Err(ExportError::ResourceNotFound),
+            Source::FilePath(p) => {
+                let mut f = Self::open_read_write_file(p, true).map_err(|_| ExportError::ResourceNotFound)?;
+                let mut lines: Vec<String> = Vec::new();
+                if self.dialect.has_header {
+                    lines.push(headers.iter()
+                        .map(|h| quote_field(h, &self.dialect))
+                        .collect::<Vec<String>>()
+                        .join(&self.dialect.delimiter.to_string()));
+                }
+                lines.extend(data.iter().map(|r| Self::record_to_string(r, &self.dialect)));
+                let col = Self::join_lines(lines, self.line_ending, self.trailing_newline);
+                write!(&mut f, "{}", col).map_err(|_| ExportError::Interrupted)
             },
             Source::HttpUri(_) => todo!(),
         }
     }
-    
+
 }
\ No newline at end of file
@@ -0,0 +1,431 @@
+use std::{cmp::Ordering, fmt::Write};
+
+use chrono::{
+    Datelike, Days, Local, Months, NaiveDate, NaiveDateTime, NaiveTime, TimeDelta, Timelike,
+};
+
+use crate::{errors::QueryError, traits::ArithmeticOperator, utils::{Interval, Value}};
+
+type Body = fn(&[Value]) -> Result<Value, QueryError>;
+
+/// Scalar function which may be called in expressions
+pub struct ScalarFunction {
+    pub name: &'static str,
+    /// Least and greatest amounts of arguments
+    pub arity: (usize, usize),
+    /// Whether a NULL argument gives NULL without the function being called
+    pub strict: bool,
+    body: Body,
+}
+
+impl ScalarFunction {
+    const fn new(name: &'static str, arity: (usize, usize), strict: bool, body: Body) -> Self {
+        Self { name, arity, strict, body }
+    }
+
+    pub fn accepts(&self, args_amount: usize) -> bool {
+        self.arity.0 <= args_amount && args_amount <= self.arity.1
+    }
+
+    pub fn call(&self, args: &[Value]) -> Result<Value, QueryError> {
+        if !self.accepts(args.len()) {
+            return Err(QueryError::new(&format!("{} does not accept {} arguments", self.name, args.len())));
+        }
+        if self.strict && args.iter().any(|v| v.is_null()) {
+            return Ok(Value::Null);
+        }
+        (self.body)(args)
+    }
+}
+
+const FUNCTIONS: &[ScalarFunction] = &[
+    ScalarFunction::new("UPPER", (1, 1), true, |args| Ok(Value::Text(text(&args[0]).to_uppercase()))),
+    ScalarFunction::new("LOWER", (1, 1), true, |args| Ok(Value::Text(text(&args[0]).to_lowercase()))),
+    ScalarFunction::new("TRIM", (1, 2), true, |args| trim(args, true, true)),
+    ScalarFunction::new("LTRIM", (1, 2), true, |args| trim(args, true, false)),
+    ScalarFunction::new("RTRIM", (1, 2), true, |args| trim(args, false, true)),
+    ScalarFunction::new("LENGTH", (1, 1), true, length),
+    ScalarFunction::new("CHAR_LENGTH", (1, 1), true, length),
+    ScalarFunction::new("SUBSTR", (2, 3), true, substr),
+    ScalarFunction::new("SUBSTRING", (2, 3), true, substr),
+    ScalarFunction::new("REPLACE", (3, 3), true, |args| Ok(Value::Text(text(&args[0]).replace(&text(&args[1]), &text(&args[2]))))),
+    ScalarFunction::new("CONCAT", (1, usize::MAX), false, |args| Ok(Value::Text(args.iter()
+        .filter(|v| !v.is_null())
+        .map(text)
+        .collect()))),
+    ScalarFunction::new("ABS", (1, 1), true, |args| match numeric(&args[0])? {
+        Value::Integer(i) => i.checked_abs().map(Value::Integer).ok_or_else(|| QueryError::new("Integer overflow in ABS")),
+        v => Ok(Value::Float(float(&v)?.abs())),
+    }),
+    ScalarFunction::new("ROUND", (1, 2), true, round),
+    ScalarFunction::new("FLOOR", (1, 1), true, |args| match numeric(&args[0])? {
+        Value::Integer(i) => Ok(Value::Integer(i)),
+        v => Ok(Value::Float(float(&v)?.floor())),
+    }),
+    ScalarFunction::new("CEIL", (1, 1), true, ceil),
+    ScalarFunction::new("CEILING", (1, 1), true, ceil),
+    ScalarFunction::new("MOD", (2, 2), true, |args| arithmetic(&args[0], &ArithmeticOperator::Modulo, &args[1])),
+    ScalarFunction::new("COALESCE", (1, usize::MAX), false, |args| Ok(args.iter()
+        .find(|v| !v.is_null())
+        .cloned()
+        .unwrap_or(Value::Null))),
+    ScalarFunction::new("IFNULL", (2, 2), false, |args| Ok(match &args[0] {
+        Value::Null => args[1].clone(),
+        v => v.clone(),
+    })),
+    ScalarFunction::new("NULLIF", (2, 2), false, |args| Ok(match args[0].compare(&args[1]) {
+        Some(Ordering::Equal) => Value::Null,
+        _ => args[0].clone(),
+    })),
+    ScalarFunction::new("NOW", (0, 0), false, |_| Ok(Value::Timestamp(now()))),
+    ScalarFunction::new("CURRENT_TIMESTAMP", (0, 0), false, |_| Ok(Value::Timestamp(now()))),
+    ScalarFunction::new("CURRENT_DATE", (0, 0), false, |_| Ok(Value::Date(now().date()))),
+    ScalarFunction::new("DATE_TRUNC", (2, 2), true, date_trunc),
+    ScalarFunction::new("DATE_PART", (2, 2), true, date_part),
+    ScalarFunction::new("DATE_ADD", (2, 2), true, |args| arithmetic(&args[0], &ArithmeticOperator::Add, &args[1])),
+    ScalarFunction::new("DATE_SUB", (2, 2), true, |args| arithmetic(&args[0], &ArithmeticOperator::Subtract, &args[1])),
+    ScalarFunction::new("STRFTIME", (2, 2), true, strftime),
+];
+
+/// Function of the registry called `name`, whatever its case
+pub fn lookup(name: &str) -> Option<&'static ScalarFunction> {
+    FUNCTIONS.iter().find(|f| f.name.eq_ignore_ascii_case(name))
+}
+
+/// Result of the scalar function `name` applied to `args`
+pub fn call(name: &str, args: &[Value]) -> Result<Value, QueryError> {
+    lookup(name).ok_or_else(|| QueryError::new(&format!("Unknown function {}", name)))?.call(args)
+}
+
+fn text(value: &Value) -> String {
+    match value {
+        Value::Text(s) => s.clone(),
+        v => v.to_string(),
+    }
+}
+
+fn integer(value: &Value) -> Result<i64, QueryError> {
+    match numeric(value)? {
+        Value::Integer(i) => Ok(i),
+        Value::Float(f) if f.fract() == 0.0 => Ok(f as i64),
+        v => Err(QueryError::new(&format!("{} is not an integer", v))),
+    }
+}
+
+fn float(value: &Value) -> Result<f64, QueryError> {
+    match numeric(value)? {
+        Value::Integer(i) => Ok(i as f64),
+        Value::Float(f) => Ok(f),
+        v => Err(QueryError::new(&format!("{} is not a number", v))),
+    }
+}
+
+fn length(args: &[Value]) -> Result<Value, QueryError> {
+    Ok(Value::Integer(text(&args[0]).chars().count() as i64))
+}
+
+/// Text without the given characters, spaces by default, at its start and/or end
+fn trim(args: &[Value], start: bool, end: bool) -> Result<Value, QueryError> {
+    let characters: Vec<char> = match args.get(1) {
+        Some(v) => text(v).chars().collect(),
+        None => vec![' '],
+    };
+    let s = text(&args[0]);
+    let mut res = s.as_str();
+    if start {
+        res = res.trim_start_matches(characters.as_slice());
+    }
+    if end {
+        res = res.trim_end_matches(characters.as_slice());
+    }
+    Ok(Value::Text(res.to_string()))
+}
+
+/// Characters from the 1-based position `args[1]`, all of them or only `args[2]` of them
+fn substr(args: &[Value]) -> Result<Value, QueryError> {
+    let s = text(&args[0]);
+    let start = integer(&args[1])?;
+    // As in PostgreSQL, positions before the first character count in the length
+    let end = match args.get(2) {
+        Some(v) => match integer(v)? {
+            len if len < 0 => return Err(QueryError::new("Negative length given to SUBSTR")),
+            len => start.saturating_add(len),
+        },
+        None => i64::MAX,
+    };
+    let first = start.max(1);
+    Ok(Value::Text(s.chars()
+        .skip((first - 1) as usize)
+        .take(end.saturating_sub(first).max(0) as usize)
+        .collect()))
+}
+
+fn round(args: &[Value]) -> Result<Value, QueryError> {
+    let digits = match args.get(1) {
+        Some(v) => integer(v)?,
+        None => 0,
+    };
+    match numeric(&args[0])? {
+        Value::Integer(i) if digits >= 0 => Ok(Value::Integer(i)),
+        v => {
+            let factor = 10f64.powi(digits.clamp(-308, 308) as i32);
+            let rounded = (float(&v)? * factor).round() / factor;
+            match v {
+                Value::Integer(_) => Ok(Value::Integer(rounded as i64)),
+                _ => Ok(Value::Float(rounded)),
+            }
+        },
+    }
+}
+
+fn ceil(args: &[Value]) -> Result<Value, QueryError> {
+    match numeric(&args[0])? {
+        Value::Integer(i) => Ok(Value::Integer(i)),
+        v => Ok(Value::Float(float(&v)?.ceil())),
+    }
+}
+
+/// Current local time, to the second as timestamps are written
+fn now() -> NaiveDateTime {
+    let now = Local::now().naive_local();
+    now.with_nanosecond(0).unwrap_or(now)
+}
+
+/// Date or timestamp held by `value`, texts written as such included
+fn temporal(value: &Value) -> Option<Value> {
+    match value {
+        Value::Date(_) | Value::Timestamp(_) => Some(value.clone()),
+        Value::Text(s) => match Value::infer(s.trim()) {
+            v @ (Value::Date(_) | Value::Timestamp(_)) => Some(v),
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+/// Interval held by `value`, texts such as `1 day` included
+fn interval(value: &Value) -> Option<Interval> {
+    match value {
+        Value::Interval(i) => Some(*i),
+        Value::Text(s) => Interval::parse(s),
+        _ => None,
+    }
+}
+
+/// Interval of `days` days
+fn days_interval(days: i64) -> Result<Interval, QueryError> {
+    i32::try_from(days).ok()
+    .and_then(|days| Interval::of(days, "day"))
+    .ok_or_else(|| QueryError::new("Interval out of range"))
+}
+
+fn field(value: &Value) -> String {
+    text(value).trim().to_lowercase()
+}
+
+/// `ts` moved by `interval`
+fn shifted(ts: NaiveDateTime, interval: &Interval) -> Result<NaiveDateTime, QueryError> {
+    let months = Months::new(interval.months.unsigned_abs());
+    let ts = if interval.months >= 0 {
+        ts.checked_add_months(months)
+    } else {
+        ts.checked_sub_months(months)
+    }
+    .ok_or_else(|| QueryError::new("Date out of range"))?;
+    let delta = TimeDelta::try_days(interval.days as i64)
+    .and_then(|days| days.checked_add(&TimeDelta::try_seconds(interval.seconds)?))
+    .ok_or_else(|| QueryError::new("Interval out of range"))?;
+    ts.checked_add_signed(delta).ok_or_else(|| QueryError::new("Date out of range"))
+}
+
+/// Date or timestamp moved by an interval, or days between two dates.
+/// `None` when the operands are not temporal values.
+fn temporal_arithmetic(left: &Value, op: &ArithmeticOperator, right: &Value) -> Result<Option<Value>, QueryError> {
+    let midnight = |d: NaiveDate| d.and_time(NaiveTime::MIN);
+    let as_interval = |v: &Value| interval(v).ok_or_else(|| QueryError::new(&format!("{} is not an interval", v)));
+    let overflow = || QueryError::new("Interval out of range");
+    let moved = |v: Value, i: Interval| -> Result<Value, QueryError> {
+        match v {
+            // Dates stay dates unless a time of the day is added to them
+            Value::Date(d) if i.seconds == 0 => Ok(Value::Date(shifted(midnight(d), &i)?.date())),
+            Value::Date(d) => Ok(Value::Timestamp(shifted(midnight(d), &i)?)),
+            Value::Timestamp(ts) => Ok(Value::Timestamp(shifted(ts, &i)?)),
+            v => Err(QueryError::new(&format!("{} is not a date", v))),
+        }
+    };
+    let res = match (temporal(left), op, temporal(right)) {
+        (Some(l), ArithmeticOperator::Add | ArithmeticOperator::Subtract, None) => {
+            let i = match (right, interval(right)) {
+                (_, Some(i)) => i,
+                (Value::Integer(days), None) => days_interval(*days)?,
+                _ => return Err(QueryError::new(&format!("Cannot add or subtract {} to a date", right))),
+            };
+            match op {
+                ArithmeticOperator::Subtract => moved(l, i.negated())?,
+                _ => moved(l, i)?,
+            }
+        },
+        (None, ArithmeticOperator::Add, Some(r)) => match (left, interval(left)) {
+            (_, Some(i)) => moved(r, i)?,
+            (Value::Integer(days), None) => moved(r, days_interval(*days)?)?,
+            _ => return Err(QueryError::new(&format!("Cannot add {} to a date", left))),
+        },
+        (Some(Value::Date(l)), ArithmeticOperator::Subtract, Some(Value::Date(r))) => Value::Integer((l - r).num_days()),
+        (Some(l), ArithmeticOperator::Subtract, Some(r)) => {
+            let as_timestamp = |v: Value| match v {
+                Value::Date(d) => midnight(d),
+                Value::Timestamp(ts) => ts,
+                _ => NaiveDateTime::MIN,
+            };
+            let delta = as_timestamp(l) - as_timestamp(r);
+            let days = delta.num_days();
+            Value::Interval(Interval {
+                months: 0,
+                days: i32::try_from(days).map_err(|_| QueryError::new("Interval out of range"))?,
+                seconds: delta.num_seconds() - days * 86400,
+            })
+        },
+        (Some(_), _, _) | (_, _, Some(_)) => return Err(QueryError::new(&format!("Invalid operation on dates : {} {} {}", left, op, right))),
+        (None, _, None) => match (left, op, right) {
+            (Value::Interval(l), ArithmeticOperator::Add, r) => Value::Interval(l.add(&as_interval(r)?).ok_or_else(overflow)?),
+            (Value::Interval(l), ArithmeticOperator::Subtract, r) => Value::Interval(l.add(&as_interval(r)?.negated()).ok_or_else(overflow)?),
+            (l, ArithmeticOperator::Add, Value::Interval(r)) => Value::Interval(as_interval(l)?.add(r).ok_or_else(overflow)?),
+            (Value::Interval(_), _, _) | (_, _, Value::Interval(_)) => return Err(QueryError::new(&format!("Invalid operation on intervals : {} {} {}", left, op, right))),
+            _ => return Ok(None),
+        },
+    };
+    Ok(Some(res))
+}
+
+/// Date or timestamp truncated to the start of the year, quarter, month, week, day, hour, minute or second
+fn date_trunc(args: &[Value]) -> Result<Value, QueryError> {
+    let value = temporal(&args[1]).ok_or_else(|| not_temporal(&args[1]))?;
+    let ts = match value {
+        Value::Date(d) => d.and_time(NaiveTime::MIN),
+        Value::Timestamp(ts) => ts,
+        _ => return Err(not_temporal(&args[1])),
+    };
+    let date = ts.date();
+    let truncated_date = |d: Option<NaiveDate>| d.map(|d| d.and_time(NaiveTime::MIN)).ok_or_else(|| QueryError::new("Date out of range"));
+    let truncated_time = |h: u32, m: u32, s: u32| NaiveTime::from_hms_opt(h, m, s)
+    .map(|t| date.and_time(t))
+    .ok_or_else(|| QueryError::new("Invalid time of the day"));
+    let res = match field(&args[0]).as_str() {
+        "year" => truncated_date(NaiveDate::from_ymd_opt(date.year(), 1, 1))?,
+        "quarter" => truncated_date(NaiveDate::from_ymd_opt(date.year(), (date.month() - 1) / 3 * 3 + 1, 1))?,
+        "month" => truncated_date(NaiveDate::from_ymd_opt(date.year(), date.month(), 1))?,
+        "week" => truncated_date(date.checked_sub_days(Days::new(date.weekday().num_days_from_monday() as u64)))?,
+        "day" => truncated_date(Some(date))?,
+        "hour" => truncated_time(ts.hour(), 0, 0)?,
+        "minute" => truncated_time(ts.hour(), ts.minute(), 0)?,
+        "second" => truncated_time(ts.hour(), ts.minute(), ts.second())?,
+        other => return Err(QueryError::new(&format!("Unknown field {} for DATE_TRUNC", other))),
+    };
+    match value {
+        Value::Date(_) if res.time() == NaiveTime::MIN => Ok(Value::Date(res.date())),
+        _ => Ok(Value::Timestamp(res)),
+    }
+}
+
+/// Part of a date or timestamp, as given by `EXTRACT(field FROM value)`
+fn date_part(args: &[Value]) -> Result<Value, QueryError> {
+    let ts = match temporal(&args[1]).ok_or_else(|| not_temporal(&args[1]))? {
+        Value::Date(d) => d.and_time(NaiveTime::MIN),
+        Value::Timestamp(ts) => ts,
+        _ => return Err(not_temporal(&args[1])),
+    };
+    let part = match field(&args[0]).as_str() {
+        "year" => ts.year() as i64,
+        "quarter" => (ts.month() as i64 - 1) / 3 + 1,
+        "month" => ts.month() as i64,
+        "week" => ts.iso_week().week() as i64,
+        "day" => ts.day() as i64,
+        "hour" => ts.hour() as i64,
+        "minute" => ts.minute() as i64,
+        "second" => ts.second() as i64,
+        "dow" => ts.weekday().num_days_from_sunday() as i64,
+        "isodow" => ts.weekday().number_from_monday() as i64,
+        "doy" => ts.ordinal() as i64,
+        "epoch" => ts.and_utc().timestamp(),
+        other => return Err(QueryError::new(&format!("Unknown field {} for EXTRACT", other))),
+    };
+    Ok(Value::Integer(part))
+}
+
+/// Date or timestamp written with a `strftime` format
+fn strftime(args: &[Value]) -> Result<Value, QueryError> {
+    // Dates are formatted as timestamps at midnight, so that formats may hold the time of the day
+    let ts = match temporal(&args[1]).ok_or_else(|| not_temporal(&args[1]))? {
+        Value::Date(d) => d.and_time(NaiveTime::MIN),
+        Value::Timestamp(ts) => ts,
+        _ => return Err(not_temporal(&args[1])),
+    };
+    // Formatting fails on unknown specifiers, such as time zones which timestamps do not have
+    let mut res = String::new();
+    write!(res, "{}", ts.format(&text(&args[0]))).map_err(|_| QueryError::new(&format!("Invalid format {}", args[0])))?;
+    Ok(Value::Text(res))
+}
+
+fn not_temporal(value: &Value) -> QueryError {
+    QueryError::new(&format!("{} is not a date", value))
+}
+
+/// Number held by `value`, texts written as numbers included
+fn numeric(value: &Value) -> Result<Value, QueryError> {
+    match value {
+        Value::Integer(_) | Value::Float(_) | Value::Null => Ok(value.clone()),
+        Value::Decimal(f, _) => Ok(Value::Float(*f)),
+        Value::Text(s) => match Value::infer(s.trim()) {
+            v @ (Value::Integer(_) | Value::Float(_) | Value::Decimal(..)) => numeric(&v),
+            _ => s.trim().parse::<f64>().map(Value::Float).map_err(|_| QueryError::new(&format!("{} is not a number", s))),
+        },
+        v => Err(QueryError::new(&format!("{} is not a number", v))),
+    }
+}
+
+/// Result of an arithmetic operation, integers giving integers unless one of the operands is a float
+pub fn arithmetic(left: &Value, op: &ArithmeticOperator, right: &Value) -> Result<Value, QueryError> {
+    if left.is_null() || right.is_null() {
+        return Ok(Value::Null);
+    }
+    if *op == ArithmeticOperator::Concat {
+        return Ok(Value::Text(format!("{}{}", left, right)));
+    }
+    if let Some(res) = temporal_arithmetic(left, op, right)? {
+        return Ok(res);
+    }
+    match (numeric(left)?, numeric(right)?) {
+        (Value::Integer(l), Value::Integer(r)) => match op {
+            ArithmeticOperator::Add => l.checked_add(r),
+            ArithmeticOperator::Subtract => l.checked_sub(r),
+            ArithmeticOperator::Multiply => l.checked_mul(r),
+            ArithmeticOperator::Divide => l.checked_div(r),
+            ArithmeticOperator::Modulo => l.checked_rem(r),
+            ArithmeticOperator::Concat => None,
+        }
+        .map(Value::Integer)
+        .ok_or_else(|| match op {
+            ArithmeticOperator::Divide | ArithmeticOperator::Modulo if r == 0 => QueryError::new("Division by zero"),
+            _ => QueryError::new(&format!("Integer overflow in {} {} {}", l, op, r)),
+        }),
+        (l, r) => {
+            let as_float = |v: Value| match v {
+                Value::Integer(i) => i as f64,
+                Value::Float(f) => f,
+                _ => f64::NAN,
+            };
+            let (l, r) = (as_float(l), as_float(r));
+            let res = match op {
+                ArithmeticOperator::Add => l + r,
+                ArithmeticOperator::Subtract => l - r,
+                ArithmeticOperator::Multiply => l * r,
+                ArithmeticOperator::Divide if r == 0.0 => return Err(QueryError::new("Division by zero")),
+                ArithmeticOperator::Divide => l / r,
+                ArithmeticOperator::Modulo if r == 0.0 => return Err(QueryError::new("Division by zero")),
+                ArithmeticOperator::Modulo => l % r,
+                ArithmeticOperator::Concat => return Err(QueryError::new("Invalid concatenation")),
+            };
+            Ok(Value::Float(res))
+        },
+    }
+}
@@ -3,4 +3,7 @@ pub mod errors;
 pub mod entities;
 pub mod traits;
 pub mod file_parsing;
-pub mod commands;
\ No newline at end of file
+pub mod commands;
+pub mod engine;
+pub mod schema;
+pub mod functions;
\ No newline at end of file
@@ -1,5 +1,125 @@
-pub fn main() {
-    let col_name = ".";
-    let split_res: Vec<&str> = col_name.split(".").collect();
-    println!("{:?}", split_res);
-}
\ No newline at end of file
+use std::{fs, path::PathBuf, process::ExitCode};
+
+use clap::Parser;
+use csql::{engine::Session, errors::ExecutionError};
+use rustyline::{error::ReadlineError, DefaultEditor};
+
+/// Runs SQL statements against the CSV files of a directory.
+#[derive(Parser)]
+#[command(name = "csql", version, about)]
+struct Cli {
+    /// Directory holding the CSV files to query
+    #[arg(default_value = ".")]
+    data_dir: String,
+    /// Runs the given SQL statements and exits
+    #[arg(short, long, value_name = "SQL", conflicts_with = "file")]
+    execute: Option<String>,
+    /// Runs the SQL script stored in the given file and exits
+    #[arg(short, long, value_name = "PATH")]
+    file: Option<PathBuf>,
+}
+
+const SHELL_HELP: &str = "\
+Statements end with ';' and may span several lines.
+.tables    List the tables of the directory
+.help      Show this message
+.exit      Leave the shell";
+
+fn run_script(session: &mut Session, sql: &str) -> Result<(), ExecutionError> {
+    for st in Session::parse(sql)?.iter() {
+        println!("{}", session.run_statement(st)?);
+    }
+    Ok(())
+}
+
+fn run_shell(session: &mut Session) -> rustyline::Result<()> {
+    let mut editor = DefaultEditor::new()?;
+    let history_path = std::env::var("HOME")
+    .ok()
+    .map(|home| PathBuf::from(home).join(".csql_history"));
+    if let Some(p) = &history_path {
+        // A missing history file only means this is the first session
+        let _ = editor.load_history(p);
+    }
+    let mut statement = String::new();
+    loop {
+        let prompt = if statement.is_empty() { "csql> " } else { "  ... " };
+        match editor.readline(prompt) {
+            Ok(line) => {
+                let trimmed = line.trim();
+                if statement.is_empty() {
+                    match trimmed {
+                        "" => continue,
+                        ".exit" | ".quit" => break,
+                        ".help" => {
+                            println!("{}", SHELL_HELP);
+                            continue;
+                        },
+                        ".tables" => {
+                            session.store()
+                            .tables_names()
+                            .iter()
+                            .for_each(|name| println!("{}", name));
+                            continue;
+                        },
+                        _ => (),
+                    }
+                }
+                statement.push_str(&line);
+                statement.push('\n');
+                if trimmed.ends_with(';') {
+                    editor.add_history_entry(statement.trim())?;
+                    if let Err(e) = run_script(session, &statement) {
+                        eprintln!("{}", e);
+                    }
+                    statement.clear();
+                }
+            },
+            Err(ReadlineError::Interrupted) => statement.clear(),
+            Err(ReadlineError::Eof) => break,
+            Err(e) => return Err(e),
+        }
+    }
+    if let Some(p) = &history_path {
+        editor.save_history(p)?;
+    }
+    Ok(())
+}
+
+pub fn main() -> ExitCode {
+    let cli = Cli::parse();
+    let mut session = match Session::from_directory(&cli.data_dir) {
+        Ok(s) => s,
+        Err(e) => {
+            eprintln!("Could not open {} : {}", cli.data_dir, e);
+            return ExitCode::FAILURE;
+        },
+    };
+    let script = match (&cli.execute, &cli.file) {
+        (Some(sql), _) => Some(sql.clone()),
+        (None, Some(path)) => match fs::read_to_string(path) {
+            Ok(content) => Some(content),
+            Err(e) => {
+                eprintln!("Could not read {} : {}", path.display(), e);
+                return ExitCode::FAILURE;
+            },
+        },
+        (None, None) => None,
+    };
+    match script {
+        Some(sql) => match run_script(&mut session, &sql) {
+            Ok(_) => ExitCode::SUCCESS,
+            Err(e) => {
+                eprintln!("{}", e);
+                ExitCode::FAILURE
+            },
+        },
+        None => match run_shell(&mut session) {
+            Ok(_) => ExitCode::SUCCESS,
+            Err(e) => {
+                eprintln!("{}", e);
+                ExitCode::FAILURE
+            },
+        },
+    }
+}
@@ -0,0 +1,183 @@
+use std::{collections::HashSet, fmt::Display};
+
+use chrono::{NaiveDate, NaiveDateTime};
+use serde::{Serialize, Deserialize};
+
+use crate::utils::{Value, DATE_FORMAT, TIMESTAMP_FORMAT};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ColumnType {
+    Integer,
+    Float,
+    Boolean,
+    Text,
+    Date,
+    Timestamp,
+}
+
+impl ColumnType {
+    /// `value` converted to this type, `None` when it cannot be.
+    /// Texts are read the way they are written in the data files, with the given date and timestamp formats,
+    /// dates and timestamps written in the ISO way of SQL being accepted as well.
+    pub fn coerce(&self, value: &Value, date_format: &str, timestamp_format: &str) -> Option<Value> {
+        match (self, value) {
+            (_, Value::Null) => Some(Value::Null),
+            (_, Value::Text(s)) => self.parse(s, date_format, timestamp_format)
+            .or_else(|| self.parse(s, DATE_FORMAT, TIMESTAMP_FORMAT)),
+            (ColumnType::Integer, Value::Integer(_))
+            | (ColumnType::Float, Value::Float(_) | Value::Decimal(..))
+            | (ColumnType::Boolean, Value::Boolean(_))
+            | (ColumnType::Date, Value::Date(_))
+            | (ColumnType::Timestamp, Value::Timestamp(_)) => Some(value.clone()),
+            (ColumnType::Integer, Value::Float(f) | Value::Decimal(f, _)) if f.fract() == 0.0 => Some(Value::Integer(*f as i64)),
+            (ColumnType::Float, Value::Integer(i)) => Some(Value::Float(*i as f64)),
+            (ColumnType::Date, Value::Timestamp(ts)) => Some(Value::Date(ts.date())),
+            (ColumnType::Timestamp, Value::Date(d)) => Some(Value::Timestamp(d.and_time(Default::default()))),
+            (ColumnType::Text, Value::Date(d)) => Some(Value::Text(d.format(date_format).to_string())),
+            (ColumnType::Text, Value::Timestamp(ts)) => Some(Value::Text(ts.format(timestamp_format).to_string())),
+            (_, v) => self.parse(&v.to_string(), date_format, timestamp_format),
+        }
+    }
+
+    /// Value of this type written as `raw`, so that texts and floats are kept as they are written
+    fn parse(&self, raw: &str, date_format: &str, timestamp_format: &str) -> Option<Value> {
+        match self {
+            ColumnType::Integer => raw.trim().parse::<i64>().ok().map(Value::Integer),
+            ColumnType::Float => raw.trim().parse::<f64>().ok().map(|f| match Value::Float(f) {
+                v if v.to_string() == raw => v,
+                _ => Value::Decimal(f, raw.to_string()),
+            }),
+            ColumnType::Boolean => raw.trim().to_lowercase().parse::<bool>().ok().map(Value::Boolean),
+            ColumnType::Text => Some(Value::Text(raw.to_string())),
+            ColumnType::Date => NaiveDate::parse_from_str(raw.trim(), date_format).ok().map(Value::Date),
+            ColumnType::Timestamp => NaiveDateTime::parse_from_str(raw.trim(), timestamp_format).ok().map(Value::Timestamp),
+        }
+    }
+}
+
+impl Display for ColumnType {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ColumnType::Integer => write!(f, "integer"),
+            ColumnType::Float => write!(f, "float"),
+            ColumnType::Boolean => write!(f, "boolean"),
+            ColumnType::Text => write!(f, "text"),
+            ColumnType::Date => write!(f, "date"),
+            ColumnType::Timestamp => write!(f, "timestamp"),
+        }
+    }
+}
+
+fn nullable_by_default() -> bool {
+    true
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ColumnSchema {
+    pub name: String,
+    #[serde(rename = "type")]
+    pub column_type: ColumnType,
+    #[serde(default = "nullable_by_default")]
+    pub nullable: bool,
+    #[serde(default)]
+    pub default: Option<serde_json::Value>,
+}
+
+impl ColumnSchema {
+    /// Value given to the column when an inserted record does not mention it
+    pub fn default_value(&self, date_format: &str, timestamp_format: &str) -> Result<Value, String> {
+        let raw = match &self.default {
+            None | Some(serde_json::Value::Null) => return Ok(Value::Null),
+            Some(serde_json::Value::Bool(b)) => Value::Boolean(*b),
+            Some(serde_json::Value::Number(n)) => Value::infer(&n.to_string()),
+            Some(serde_json::Value::String(s)) => Value::Text(s.clone()),
+            Some(other) => return Err(format!("The default value {} of column {} is not a scalar.", other, self.name)),
+        };
+        self.conform(&raw, date_format, timestamp_format)
+    }
+
+    /// `value` converted to the column type, as long as it is allowed in the column
+    pub fn conform(&self, value: &Value, date_format: &str, timestamp_format: &str) -> Result<Value, String> {
+        match self.column_type.coerce(value, date_format, timestamp_format) {
+            Some(Value::Null) if !self.nullable => Err(format!("The column {} cannot be NULL.", self.name)),
+            Some(v) => Ok(v),
+            None => Err(format!("The column {} expects a {} value, found '{}'.", self.name, self.column_type, value)),
+        }
+    }
+}
+
+/// Columns of a table, as declared in the `tables` section of `config.json`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TableSchema {
+    pub columns: Vec<ColumnSchema>,
+    #[serde(default)]
+    pub primary_key: Vec<String>,
+    /// Formats of the dates and timestamps in the data file, given by its dialect
+    #[serde(skip, default = "default_date_format")]
+    pub date_format: String,
+    #[serde(skip, default = "default_timestamp_format")]
+    pub timestamp_format: String,
+}
+
+fn default_date_format() -> String {
+    DATE_FORMAT.to_string()
+}
+
+fn default_timestamp_format() -> String {
+    TIMESTAMP_FORMAT.to_string()
+}
+
+impl TableSchema {
+    /// Schema of a data file writing its dates and timestamps with the given formats
+    pub fn with_formats(mut self, date_format: &str, timestamp_format: &str) -> Self {
+        self.date_format = date_format.to_string();
+        self.timestamp_format = timestamp_format.to_string();
+        self
+    }
+
+    pub fn columns_names(&self) -> Vec<&str> {
+        self.columns.iter().map(|c| c.name.as_str()).collect()
+    }
+
+    /// Checks the declaration itself is consistent
+    pub fn validate(&self) -> Result<(), String> {
+        let mut names = HashSet::new();
+        for c in self.columns.iter() {
+            if !names.insert(c.name.as_str()) {
+                return Err(format!("The column {} is declared twice.", c.name));
+            }
+            c.default_value(&self.date_format, &self.timestamp_format)?;
+        }
+        for key in self.primary_key.iter() {
+            if !names.contains(key.as_str()) {
+                return Err(format!("The primary key column {} is not declared.", key));
+            }
+        }
+        Ok(())
+    }
+
+    /// Positions of the primary key columns
+    pub fn primary_key_indexes(&self) -> Vec<usize> {
+        self.primary_key.iter()
+        .filter_map(|key| self.columns.iter().position(|c| &c.name == key))
+        .collect()
+    }
+
+    /// Values of a record converted to the declared types
+    pub fn conform(&self, values: &[Value]) -> Result<Vec<Value>, String> {
+        if values.len() != self.columns.len() {
+            return Err(format!("Expected {} values, found {}.", self.columns.len(), values.len()));
+        }
+        let conformed = self.columns.iter()
+        .zip(values.iter())
+        .map(|(c, v)| c.conform(v, &self.date_format, &self.timestamp_format))
+        .collect::<Result<Vec<Value>, String>>()?;
+        for idx in self.primary_key_indexes() {
+            if conformed[idx].is_null() {
+                return Err(format!("The primary key column {} cannot be NULL.", self.columns[idx].name));
+            }
+        }
+        Ok(conformed)
+    }
+}
@@ -1,10 +1,13 @@
-use std::collections::HashMap;
+use std::{cmp::Ordering, collections::HashMap, fmt::Display, rc::Rc};
 
-use crate::{errors::{CommitError, ExportError, LoadingError, QueryError, SerializeError}, commands::Command, utils::Value};
+use regex::Regex;
+
+use crate::{errors::{CommitError, ExecutionError, ExportError, LoadingError, QueryError, SerializeError}, commands::Command, schema::ColumnType, utils::Value};
 
 pub trait Data: Sized {
-    fn bulk_load_data(&mut self, data: &Vec<Vec<Value>>) -> Result<(), LoadingError>;
+    fn bulk_load_data(&mut self, data: &[Vec<Value>]) -> Result<(), LoadingError>;
     fn get_records_as_collection(&self) -> Vec<Vec<Value>>;
+    fn get_headers(&self) -> Vec<String>;
 }
 
 type ColumnName = String;
@@ -15,32 +18,573 @@ pub trait Queryable<T: Recordable> {
         attributes_names: &Columns,
         conditions: &Option<Condition>,
     ) -> Result<Vec<Vec<Value>>, QueryError>;
-    fn delete(&mut self, _object_name: &Option<String>, conditions: &Option<Condition>) -> Result<(), QueryError>;
+    fn delete(&mut self, _object_name: &Option<String>, conditions: &Option<Condition>) -> Result<usize, QueryError>;
     fn update(
         &mut self,
         _object_name: &Option<String>,
         new_values: HashMap<ColumnName, Value>,
         conditions: &Option<Condition>,
-    ) -> Result<(), QueryError>;
-    fn insert(&mut self, _object_name: &Option<String>, new_record: InsertElement) -> Result<(), QueryError>;
+    ) -> Result<usize, QueryError>;
+    fn insert(&mut self, _object_name: &Option<String>, new_record: InsertElement) -> Result<(), QueryError> {
+        self.insert_all(_object_name, vec![new_record]).map(|_| ())
+    }
+    /// Inserts all the records or none of them, returning how many were inserted
+    fn insert_all(&mut self, _object_name: &Option<String>, new_records: Vec<InsertElement>) -> Result<usize, QueryError>;
 }
 
 pub trait Recordable: Sized {
     fn get_record_as_collection(&self) -> Vec<Value>;
-    fn get_attr_index_from_name(&self, attr_name: &String) -> Result<usize, QueryError>;
-    fn get_attr_value(&self, attr_name: &String) -> Result<Value, QueryError>;
-    fn get_attr_values(&self, attr_names: &Vec<String>) -> Result<Vec<Value>, QueryError>;
+    fn get_attr_index_from_name(&self, attr_name: &str) -> Result<usize, QueryError>;
+    fn get_attr_value(&self, attr_name: &str) -> Result<Value, QueryError>;
+    fn get_attr_values(&self, attr_names: &[String]) -> Result<Vec<Value>, QueryError>;
     fn update_values(&mut self, new_values: &HashMap<ColumnName, Value>) -> Result<(), QueryError>;
     fn satisfy_conditions(&self, cond: &Condition) -> Result<bool, QueryError>;
+    fn evaluate(&self, expr: &Expression) -> Result<Value, QueryError>;
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ArithmeticOperator {
+    Add,
+    Subtract,
+    Multiply,
+    Divide,
+    Modulo,
+    Concat,
+}
+
+impl Display for ArithmeticOperator {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ArithmeticOperator::Add => write!(f, "+"),
+            ArithmeticOperator::Subtract => write!(f, "-"),
+            ArithmeticOperator::Multiply => write!(f, "*"),
+            ArithmeticOperator::Divide => write!(f, "/"),
+            ArithmeticOperator::Modulo => write!(f, "%"),
+            ArithmeticOperator::Concat => write!(f, "||"),
+        }
+    }
+}
+
+/// Value computed for each record
+#[derive(Debug, Clone, PartialEq)]
+pub enum Expression {
+    Column(String),
+    Literal(Value),
+    BinaryOp {
+        left: Box<Expression>,
+        op: ArithmeticOperator,
+        right: Box<Expression>,
+    },
+    Negate(Box<Expression>),
+    /// Scalar function, whose name is upper case
+    Function {
+        name: String,
+        args: Vec<Expression>,
+    },
+    Cast(Box<Expression>, ColumnType),
+    /// Result of the first branch whose condition holds, `otherwise` or NULL when none does
+    Case {
+        branches: Vec<(Condition, Expression)>,
+        otherwise: Option<Box<Expression>>,
+    },
+    /// Single value returned by a subquery, NULL when it returns no row
+    Subquery(Subquery),
+    /// Truth value of a condition, NULL when it is unknown
+    Condition(Box<Condition>),
+}
+
+/// Query nested in another statement, which may refer to the columns of the record it is computed for
+#[derive(Debug, Clone)]
+pub struct Subquery {
+    pub sql: String,
+    pub command: Rc<Command>,
+}
+
+/// Subqueries are compared by their SQL
+impl PartialEq for Subquery {
+    fn eq(&self, other: &Self) -> bool {
+        self.sql == other.sql
+    }
+}
+
+/// Values replacing the columns and subqueries of expressions, as the ones of the enclosing statement in a correlated subquery
+pub trait Binding {
+    /// Value of the column, `None` when the column is kept
+    fn column(&self, name: &str) -> Option<Value>;
+    /// Rows returned by the subquery, `None` when the subquery is kept
+    fn rows(&self, subquery: &Subquery) -> Result<Option<Vec<Vec<Value>>>, ExecutionError>;
+}
+
+/// Column or subquery read by an expression
+#[derive(Debug, Clone, Copy)]
+pub enum Reference<'a> {
+    Column(&'a str),
+    Subquery(&'a Subquery),
+}
+
+/// Values of the single column of subquery rows
+fn single_column(rows: Vec<Vec<Value>>) -> Result<Vec<Value>, QueryError> {
+    rows.into_iter()
+    .map(|row| match <[Value; 1]>::try_from(row) {
+        Ok([v]) => Ok(v),
+        Err(row) => Err(QueryError::new(&format!("Subquery returning {} columns instead of one", row.len()))),
+    })
+    .collect()
+}
+
+impl Expression {
+    /// Copy of the expression in which columns and subqueries are replaced by the values `binding` gives
+    pub fn bound(&self, binding: &impl Binding) -> Result<Expression, ExecutionError> {
+        let bound = |e: &Expression| -> Result<Box<Expression>, ExecutionError> { Ok(Box::new(e.bound(binding)?)) };
+        Ok(match self {
+            Expression::Column(col) => match binding.column(col) {
+                Some(v) => Expression::Literal(v),
+                None => self.clone(),
+            },
+            Expression::Literal(_) => self.clone(),
+            Expression::BinaryOp { left, op, right } => Expression::BinaryOp { left: bound(left)?, op: *op, right: bound(right)? },
+            Expression::Negate(e) => Expression::Negate(bound(e)?),
+            Expression::Function { name, args } => Expression::Function {
+                name: name.clone(),
+                args: args.iter().map(|arg| arg.bound(binding)).collect::<Result<Vec<Expression>, ExecutionError>>()?,
+            },
+            Expression::Cast(e, to) => Expression::Cast(bound(e)?, *to),
+            Expression::Case { branches, otherwise } => Expression::Case {
+                branches: branches.iter()
+                .map(|(cond, result)| Ok((cond.bound(binding)?, result.bound(binding)?)))
+                .collect::<Result<Vec<(Condition, Expression)>, ExecutionError>>()?,
+                otherwise: otherwise.as_ref().map(|e| bound(e)).transpose()?,
+            },
+            Expression::Subquery(query) => match binding.rows(query)? {
+                Some(rows) => match single_column(rows)?.as_slice() {
+                    [] => Expression::Literal(Value::Null),
+                    [v] => Expression::Literal(v.clone()),
+                    _ => return Err(ExecutionError::Querying(QueryError::new(&format!("Subquery returning several rows : {}", query.sql)))),
+                },
+                None => self.clone(),
+            },
+            Expression::Condition(cond) => Expression::Condition(Box::new(cond.bound(binding)?)),
+        })
+    }
+
+    /// Whether a subquery has to be run to compute the expression
+    pub fn has_subquery(&self) -> bool {
+        match self {
+            Expression::Column(_) | Expression::Literal(_) => false,
+            Expression::Subquery(_) => true,
+            Expression::Condition(cond) => cond.has_subquery(),
+            Expression::BinaryOp { left, right, .. } => left.has_subquery() || right.has_subquery(),
+            Expression::Negate(e) | Expression::Cast(e, _) => e.has_subquery(),
+            Expression::Function { args, .. } => args.iter().any(|arg| arg.has_subquery()),
+            Expression::Case { branches, otherwise } => branches.iter().any(|(cond, result)| cond.has_subquery() || result.has_subquery())
+                || otherwise.as_ref().is_some_and(|e| e.has_subquery()),
+        }
+    }
+
+    /// First column read by the expression out of the parts `known` accepts, subqueries aside
+    pub fn column_outside(&self, known: &impl Fn(&Expression) -> bool) -> Option<&str> {
+        if known(self) {
+            return None;
+        }
+        match self {
+            Expression::Column(col) => Some(col),
+            Expression::Literal(_) | Expression::Subquery(_) => None,
+            Expression::BinaryOp { left, right, .. } => left.column_outside(known).or_else(|| right.column_outside(known)),
+            Expression::Negate(e) | Expression::Cast(e, _) => e.column_outside(known),
+            Expression::Function { args, .. } => args.iter().find_map(|arg| arg.column_outside(known)),
+            Expression::Case { branches, otherwise } => branches.iter()
+            .find_map(|(cond, result)| cond.column_outside(known).or_else(|| result.column_outside(known)))
+            .or_else(|| otherwise.as_ref().and_then(|e| e.column_outside(known))),
+            Expression::Condition(cond) => cond.column_outside(known),
+        }
+    }
+
+    /// Adds the columns and subqueries read by the expression to `found`, without looking into the subqueries
+    pub fn references<'a>(&'a self, found: &mut Vec<Reference<'a>>) {
+        match self {
+            Expression::Column(col) => found.push(Reference::Column(col)),
+            Expression::Literal(_) => (),
+            Expression::Subquery(query) => found.push(Reference::Subquery(query)),
+            Expression::BinaryOp { left, right, .. } => {
+                left.references(found);
+                right.references(found);
+            },
+            Expression::Negate(e) | Expression::Cast(e, _) => e.references(found),
+            Expression::Function { args, .. } => args.iter().for_each(|arg| arg.references(found)),
+            Expression::Case { branches, otherwise } => {
+                for (cond, result) in branches.iter() {
+                    cond.references(found);
+                    result.references(found);
+                }
+                if let Some(e) = otherwise {
+                    e.references(found);
+                }
+            },
+            Expression::Condition(cond) => cond.references(found),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ComparisonOperator {
+    Equal,
+    NotEqual,
+    GreaterThan,
+    GreaterOrEqual,
+    LessThan,
+    LessOrEqual,
+}
+
+impl ComparisonOperator {
+    /// Whether two values ordered as `ordering` satisfy the comparison
+    pub fn holds(&self, ordering: Ordering) -> bool {
+        match self {
+            ComparisonOperator::Equal => ordering == Ordering::Equal,
+            ComparisonOperator::NotEqual => ordering != Ordering::Equal,
+            ComparisonOperator::GreaterThan => ordering == Ordering::Greater,
+            ComparisonOperator::GreaterOrEqual => ordering != Ordering::Less,
+            ComparisonOperator::LessThan => ordering == Ordering::Less,
+            ComparisonOperator::LessOrEqual => ordering != Ordering::Greater,
+        }
+    }
+
+    /// Operator giving the same result once the operands are swapped
+    pub fn mirrored(&self) -> Self {
+        match self {
+            ComparisonOperator::Equal => ComparisonOperator::Equal,
+            ComparisonOperator::NotEqual => ComparisonOperator::NotEqual,
+            ComparisonOperator::GreaterThan => ComparisonOperator::LessThan,
+            ComparisonOperator::GreaterOrEqual => ComparisonOperator::LessOrEqual,
+            ComparisonOperator::LessThan => ComparisonOperator::GreaterThan,
+            ComparisonOperator::LessOrEqual => ComparisonOperator::GreaterOrEqual,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
 pub enum Condition {
-    Equal(String, String),
-    GreaterThan(String, String),
-    LessThan(String, String),
+    Compare(Expression, ComparisonOperator, Expression),
+    /// Value among the listed ones
+    In(Expression, Vec<Expression>),
+    /// Value within the bounds, both included
+    Between(Expression, Expression, Expression),
+    IsNull(Expression),
+    /// Value matching a LIKE pattern, where `%` stands for any text and `_` for any character
+    Like {
+        expr: Expression,
+        pattern: String,
+        escape: Option<char>,
+        case_insensitive: bool,
+    },
+    /// Value holding a match of the regular expression
+    Regex(Expression, Regex),
+    Not(Box<Condition>),
     Or(Box<Condition>, Box<Condition>),
     And(Box<Condition>, Box<Condition>),
+    /// Value among the ones returned by a subquery
+    InSubquery(Expression, Subquery),
+    /// Subquery returning at least one row
+    Exists(Subquery),
+    /// Boolean expression, such as a boolean column or the outcome of EXISTS once its subquery is run
+    Truth(Expression),
+}
+
+/// Regular expressions are compared by their pattern
+impl PartialEq for Condition {
+    fn eq(&self, other: &Self) -> bool {
+        match (self, other) {
+            (Condition::Compare(l1, op1, r1), Condition::Compare(l2, op2, r2)) => l1 == l2 && op1 == op2 && r1 == r2,
+            (Condition::In(e1, l1), Condition::In(e2, l2)) => e1 == e2 && l1 == l2,
+            (Condition::Between(e1, low1, high1), Condition::Between(e2, low2, high2)) => e1 == e2 && low1 == low2 && high1 == high2,
+            (Condition::IsNull(e1), Condition::IsNull(e2)) => e1 == e2,
+            (
+                Condition::Like { expr: e1, pattern: p1, escape: esc1, case_insensitive: ci1 },
+                Condition::Like { expr: e2, pattern: p2, escape: esc2, case_insensitive: ci2 },
+            ) => e1 == e2 && p1 == p2 && esc1 == esc2 && ci1 == ci2,
+            (Condition::Regex(e1, re1), Condition::Regex(e2, re2)) => e1 == e2 && re1.as_str() == re2.as_str(),
+            (Condition::Not(c1), Condition::Not(c2)) => c1 == c2,
+            (Condition::Or(l1, r1), Condition::Or(l2, r2)) | (Condition::And(l1, r1), Condition::And(l2, r2)) => l1 == l2 && r1 == r2,
+            (Condition::InSubquery(e1, q1), Condition::InSubquery(e2, q2)) => e1 == e2 && q1 == q2,
+            (Condition::Exists(q1), Condition::Exists(q2)) => q1 == q2,
+            (Condition::Truth(e1), Condition::Truth(e2)) => e1 == e2,
+            _ => false,
+        }
+    }
+}
+
+impl Condition {
+    /// Comparison of two expressions, normalized so that a literal compared to anything else is on the right
+    pub fn comparison(left: Expression, op: ComparisonOperator, right: Expression) -> Self {
+        match (&left, &right) {
+            (Expression::Literal(_), Expression::Literal(_)) => Condition::Compare(left, op, right),
+            (Expression::Literal(_), _) => Condition::Compare(right, op.mirrored(), left),
+            _ => Condition::Compare(left, op, right),
+        }
+    }
+
+    /// Comparison of a column to a literal value
+    pub fn column_compared(column: &str, op: ComparisonOperator, value: Value) -> Self {
+        Condition::comparison(Expression::Column(column.to_string()), op, Expression::Literal(value))
+    }
+
+    /// Copy of the condition in which columns and subqueries are replaced by the values `binding` gives
+    pub fn bound(&self, binding: &impl Binding) -> Result<Condition, ExecutionError> {
+        let bound = |c: &Condition| -> Result<Box<Condition>, ExecutionError> { Ok(Box::new(c.bound(binding)?)) };
+        Ok(match self {
+            Condition::Compare(left, op, right) => Condition::Compare(left.bound(binding)?, *op, right.bound(binding)?),
+            Condition::In(expr, values) => Condition::In(
+                expr.bound(binding)?,
+                values.iter().map(|v| v.bound(binding)).collect::<Result<Vec<Expression>, ExecutionError>>()?,
+            ),
+            Condition::Between(expr, low, high) => Condition::Between(expr.bound(binding)?, low.bound(binding)?, high.bound(binding)?),
+            Condition::IsNull(expr) => Condition::IsNull(expr.bound(binding)?),
+            Condition::Like { expr, pattern, escape, case_insensitive } => Condition::Like {
+                expr: expr.bound(binding)?,
+                pattern: pattern.clone(),
+                escape: *escape,
+                case_insensitive: *case_insensitive,
+            },
+            Condition::Regex(expr, re) => Condition::Regex(expr.bound(binding)?, re.clone()),
+            Condition::Not(cond) => Condition::Not(bound(cond)?),
+            Condition::Or(left, right) => Condition::Or(bound(left)?, bound(right)?),
+            Condition::And(left, right) => Condition::And(bound(left)?, bound(right)?),
+            Condition::InSubquery(expr, query) => match binding.rows(query)? {
+                Some(rows) => Condition::In(
+                    expr.bound(binding)?,
+                    single_column(rows)?.into_iter().map(Expression::Literal).collect(),
+                ),
+                None => Condition::InSubquery(expr.bound(binding)?, query.clone()),
+            },
+            Condition::Exists(query) => match binding.rows(query)? {
+                Some(rows) => Condition::Truth(Expression::Literal(Value::Boolean(!rows.is_empty()))),
+                None => self.clone(),
+            },
+            Condition::Truth(expr) => Condition::Truth(expr.bound(binding)?),
+        })
+    }
+
+    /// Whether a subquery has to be run to check the condition
+    pub fn has_subquery(&self) -> bool {
+        match self {
+            Condition::Compare(left, _, right) => left.has_subquery() || right.has_subquery(),
+            Condition::In(expr, values) => expr.has_subquery() || values.iter().any(|v| v.has_subquery()),
+            Condition::Between(expr, low, high) => expr.has_subquery() || low.has_subquery() || high.has_subquery(),
+            Condition::IsNull(expr) | Condition::Like { expr, .. } | Condition::Regex(expr, _) | Condition::Truth(expr) => expr.has_subquery(),
+            Condition::Not(cond) => cond.has_subquery(),
+            Condition::Or(left, right) | Condition::And(left, right) => left.has_subquery() || right.has_subquery(),
+            Condition::InSubquery(_, _) | Condition::Exists(_) => true,
+        }
+    }
+
+    /// First column read by the condition out of the expressions `known` accepts, subqueries aside
+    pub fn column_outside(&self, known: &impl Fn(&Expression) -> bool) -> Option<&str> {
+        match self {
+            Condition::Compare(left, _, right) => left.column_outside(known).or_else(|| right.column_outside(known)),
+            Condition::In(expr, values) => expr.column_outside(known).or_else(|| values.iter().find_map(|v| v.column_outside(known))),
+            Condition::Between(expr, low, high) => [expr, low, high].into_iter().find_map(|e| e.column_outside(known)),
+            Condition::IsNull(expr) | Condition::Like { expr, .. } | Condition::Regex(expr, _) | Condition::Truth(expr)
+            | Condition::InSubquery(expr, _) => expr.column_outside(known),
+            Condition::Not(cond) => cond.column_outside(known),
+            Condition::Or(left, right) | Condition::And(left, right) => left.column_outside(known).or_else(|| right.column_outside(known)),
+            Condition::Exists(_) => None,
+        }
+    }
+
+    /// Adds the columns and subqueries read by the condition to `found`, without looking into the subqueries
+    pub fn references<'a>(&'a self, found: &mut Vec<Reference<'a>>) {
+        match self {
+            Condition::Compare(left, _, right) => {
+                left.references(found);
+                right.references(found);
+            },
+            Condition::In(expr, values) => {
+                expr.references(found);
+                values.iter().for_each(|v| v.references(found));
+            },
+            Condition::Between(expr, low, high) => [expr, low, high].into_iter().for_each(|e| e.references(found)),
+            Condition::IsNull(expr) | Condition::Like { expr, .. } | Condition::Regex(expr, _) | Condition::Truth(expr) => expr.references(found),
+            Condition::Not(cond) => cond.references(found),
+            Condition::Or(left, right) | Condition::And(left, right) => {
+                left.references(found);
+                right.references(found);
+            },
+            Condition::InSubquery(expr, query) => {
+                expr.references(found);
+                found.push(Reference::Subquery(query));
+            },
+            Condition::Exists(query) => found.push(Reference::Subquery(query)),
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct TableReference {
+    pub name: String,
+    pub alias: Option<String>,
+}
+
+impl TableReference {
+    pub fn new(name: &str) -> Self {
+        Self { name: name.to_string(), alias: None }
+    }
+
+    /// Name under which the table columns are referenced in the query
+    pub fn reference_name(&self) -> &str {
+        self.alias.as_ref().unwrap_or(&self.name)
+    }
+}
+
+#[derive(Debug)]
+pub enum JoinKind {
+    Inner,
+    LeftOuter,
+    RightOuter,
+    FullOuter,
+    Cross,
+}
+
+#[derive(Debug)]
+pub enum JoinConstraint {
+    On(Condition),
+    Using(Vec<String>),
+    Natural,
+    None,
+}
+
+#[derive(Debug)]
+pub struct Join {
+    pub table: TableReference,
+    pub kind: JoinKind,
+    pub constraint: JoinConstraint,
+}
+
+#[derive(Debug)]
+pub struct JoinedTables {
+    pub table: TableReference,
+    pub joins: Vec<Join>,
+}
+
+#[derive(Debug, PartialEq)]
+pub enum AggregateFunction {
+    Count,
+    Sum,
+    Avg,
+    Min,
+    Max,
+}
+
+/// Value computed over each group of records, exposed as a column named the way it is written in the query (e.g. `COUNT(*)`)
+#[derive(Debug)]
+pub struct Aggregate {
+    pub name: String,
+    pub function: AggregateFunction,
+    /// Expression computed for each record and aggregated, `None` standing for `*`
+    pub argument: Option<Expression>,
+    pub distinct: bool,
+}
+
+/// Function computed for each record over the records of its partition, sorted by `order_by`.
+/// It is exposed as a column named the way it is written in the query (e.g. `ROW_NUMBER() OVER (ORDER BY id)`)
+#[derive(Debug)]
+pub struct Window {
+    pub name: String,
+    pub function: WindowFunction,
+    pub partition_by: Vec<Expression>,
+    pub order_by: Vec<OrderBy>,
+    pub frame: Frame,
+}
+
+#[derive(Debug)]
+pub enum WindowFunction {
+    RowNumber,
+    /// Rank of the record, records sorted as equal sharing the same one and leaving gaps after them
+    Rank,
+    /// Rank of the record, records sorted as equal sharing the same one without leaving gaps
+    DenseRank,
+    /// Value of `expr` for the record `offset` places before, `default` when there is none
+    Lag {
+        expr: Expression,
+        offset: usize,
+        default: Expression,
+    },
+    /// Value of `expr` for the record `offset` places after, `default` when there is none
+    Lead {
+        expr: Expression,
+        offset: usize,
+        default: Expression,
+    },
+    /// Value of the expression for the first record of the frame
+    FirstValue(Expression),
+    /// Aggregate of the records of the frame
+    Aggregate(Aggregate),
+}
+
+/// Records of the partition a window function is computed over, relative to the current one
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Frame {
+    /// Whether bounds count records, rather than groups of records sorted as equal
+    pub rows: bool,
+    pub start: FrameBound,
+    pub end: FrameBound,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum FrameBound {
+    UnboundedPreceding,
+    Preceding(usize),
+    CurrentRow,
+    Following(usize),
+    UnboundedFollowing,
+}
+
+#[derive(Debug)]
+pub enum SortKey {
+    Expression(Expression),
+    /// 1-based position of a selected column, as in `ORDER BY 2`
+    Position(usize),
+}
+
+#[derive(Debug)]
+pub struct OrderBy {
+    pub key: SortKey,
+    pub ascending: bool,
+    pub nulls_first: bool,
+}
+
+#[derive(Debug)]
+pub enum Distinct {
+    /// Only one of the identical rows of the result is kept
+    Rows,
+    /// Only the first record of each value of the expressions is kept, as in `DISTINCT ON (...)`
+    On(Vec<Expression>),
+}
+
+/// Table defined by a WITH clause for the rest of the query
+#[derive(Debug)]
+pub struct CommonTable {
+    pub name: String,
+    /// Names given to the columns, the ones of the query when empty
+    pub columns: Vec<String>,
+    pub query: Command,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SetOperator {
+    /// Rows of either query
+    Union,
+    /// Rows of the first query which the second one also returns
+    Intersect,
+    /// Rows of the first query which the second one does not return
+    Except,
+}
+
+/// Item of the select list
+#[derive(Debug, Clone)]
+pub enum Projection {
+    /// `*`, or `t.*` when restricted to the columns of the table `t`
+    Wildcard(Option<String>),
+    /// Expression, with the name of its output column
+    Expression(Expression, String),
 }
 
 #[derive(Debug)]
@@ -65,10 +609,11 @@ where
     ) -> Result<Vec<Value>, LoadingError>;
     fn collection_to_string(collection: Vec<Vec<Value>>) -> String;
     fn load_from_source(source_path: &str, source_type: SourceType) -> Result<Self, LoadingError>;
-    fn bulk_data(&self, columns_amount: usize) -> Result<Vec<Vec<Value>>, LoadingError>;
-    fn dump_data(&self, data: Vec<Vec<Value>>) -> Result<(), ExportError>;
+    fn read_headers(&self) -> Result<Vec<String>, LoadingError>;
+    fn bulk_data(&self) -> Result<Vec<Vec<Value>>, LoadingError>;
+    fn dump_data(&self, headers: &[String], data: Vec<Vec<Value>>) -> Result<(), ExportError>;
     fn commit(&mut self, new_data: &impl Data) -> Result<(), CommitError> {
-        self.dump_data(new_data.get_records_as_collection())
+        self.dump_data(&new_data.get_headers(), new_data.get_records_as_collection())
             .map_err(|_| CommitError)
     }
 }
@@ -87,10 +632,10 @@ pub trait Executable {
 }
 
 pub trait Storage {
-    fn bulk_data(&self, section_name: &String, columns_amount: usize) -> Result<Vec<Vec<Value>>, LoadingError>;
-    fn dump_data(&self, section_name: &String, data: Vec<Vec<Value>>) -> Result<(), ExportError>;
-    fn commit(&self, section_name: &String, new_data: &impl Data) -> Result<(), CommitError> {
-        self.dump_data(section_name, new_data.get_records_as_collection())
+    fn bulk_data(&self, section_name: &str) -> Result<Vec<Vec<Value>>, LoadingError>;
+    fn dump_data(&self, section_name: &str, headers: &[String], data: Vec<Vec<Value>>) -> Result<(), ExportError>;
+    fn commit(&self, section_name: &str, new_data: &impl Data) -> Result<(), CommitError> {
+        self.dump_data(section_name, &new_data.get_headers(), new_data.get_records_as_collection())
             .map_err(|_| CommitError)
     }
     fn commit_all(&self , new_data: HashMap<String, &impl Data>) -> Result<(), CommitError> {
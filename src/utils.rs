@@ -1 +1,358 @@
-pub type Value = Option<String>;
\ No newline at end of file
+use std::{cmp::Ordering, fmt::Display, hash::{Hash, Hasher}};
+
+use chrono::{NaiveDate, NaiveDateTime};
+
+pub const DATE_FORMAT: &str = "%Y-%m-%d";
+pub const TIMESTAMP_FORMAT: &str = "%Y-%m-%d %H:%M:%S";
+
+#[derive(Debug, Clone)]
+pub enum Value {
+    Null,
+    Integer(i64),
+    Float(f64),
+    /// Float written otherwise than in its shortest form, such as `1.50` or `2E3`.
+    /// It is a float in computations, its text being displayed and written back.
+    Decimal(f64, String),
+    Boolean(bool),
+    Text(String),
+    Date(NaiveDate),
+    Timestamp(NaiveDateTime),
+    Interval(Interval),
+}
+
+impl Value {
+    /// Value written as `raw` in a data file.
+    /// A type is only inferred when writing the value back gives `raw` again, so that no data is altered on commit.
+    /// Decimal numbers such as `10.00` keep their text next to their number, so that they sort and add up as numbers.
+    pub fn infer(raw: &str) -> Self {
+        Self::infer_with_formats(raw, DATE_FORMAT, TIMESTAMP_FORMAT)
+    }
+
+    /// Value written as `raw`, dates and timestamps being written with the given `strftime` formats
+    pub fn infer_with_formats(raw: &str, date_format: &str, timestamp_format: &str) -> Self {
+        if raw.is_empty() {
+            return Value::Null;
+        }
+        if let Ok(i) = raw.parse::<i64>() {
+            if i.to_string() == raw {
+                return Value::Integer(i);
+            }
+        }
+        if let Ok(f) = raw.parse::<f64>() {
+            if f.is_finite() && Value::Float(f).to_string() == raw {
+                return Value::Float(f);
+            }
+            if f.is_finite() && is_decimal(raw) {
+                return Value::Decimal(f, raw.to_string());
+            }
+        }
+        match raw {
+            "true" => return Value::Boolean(true),
+            "false" => return Value::Boolean(false),
+            _ => (),
+        }
+        if let Ok(d) = NaiveDate::parse_from_str(raw, date_format) {
+            if d.format(date_format).to_string() == raw {
+                return Value::Date(d);
+            }
+        }
+        if let Ok(ts) = NaiveDateTime::parse_from_str(raw, timestamp_format) {
+            if ts.format(timestamp_format).to_string() == raw {
+                return Value::Timestamp(ts);
+            }
+        }
+        Value::Text(raw.to_string())
+    }
+
+    pub fn is_null(&self) -> bool {
+        matches!(self, Value::Null)
+    }
+
+    /// Order of the kinds of values when sorting values of different types
+    fn type_rank(&self) -> u8 {
+        match self {
+            Value::Null => 0,
+            Value::Boolean(_) => 1,
+            Value::Integer(_) | Value::Float(_) | Value::Decimal(..) => 2,
+            Value::Date(_) | Value::Timestamp(_) => 3,
+            Value::Interval(_) => 4,
+            Value::Text(_) => 5,
+        }
+    }
+
+    /// Text converted to the type of `other`, when it is written the way values of this type are
+    fn coerce_text(text: &str, other: &Value) -> Option<Value> {
+        match other {
+            Value::Integer(_) | Value::Float(_) | Value::Decimal(..) => text.trim().parse::<f64>().ok().map(Value::Float),
+            Value::Boolean(_) => text.trim().to_lowercase().parse::<bool>().ok().map(Value::Boolean),
+            Value::Date(_) | Value::Timestamp(_) => NaiveDateTime::parse_from_str(text, TIMESTAMP_FORMAT)
+            .map(Value::Timestamp)
+            .or_else(|_| NaiveDate::parse_from_str(text, DATE_FORMAT).map(Value::Date))
+            .ok(),
+            Value::Interval(_) => Interval::parse(text).map(Value::Interval),
+            Value::Null | Value::Text(_) => None,
+        }
+    }
+
+    /// SQL comparison of two values : `None` when one of them is NULL.
+    /// Text compared to another type is converted to this type when possible, the values being incomparable otherwise.
+    pub fn compare(&self, other: &Value) -> Option<Ordering> {
+        match (self, other) {
+            (Value::Null, _) | (_, Value::Null) => None,
+            (Value::Text(s), Value::Text(o)) => Some(s.cmp(o)),
+            // Decimals are compared to texts as they are written
+            (Value::Text(s), Value::Decimal(_, o)) => Some(s.as_str().cmp(o)),
+            (Value::Text(s), o) => Self::coerce_text(s, o).map(|v| v.cmp(o)),
+            (v, Value::Text(_)) => other.compare(v).map(Ordering::reverse),
+            (v, o) if v.type_rank() == o.type_rank() => Some(v.cmp(o)),
+            _ => None,
+        }
+    }
+}
+
+/// Whether `raw` is written as a decimal number, such as `-1.50` or `2.5e3`.
+/// Numbers written with leading zeros, such as zip codes, are not.
+fn is_decimal(raw: &str) -> bool {
+    let digits = |s: &str| !s.is_empty() && s.bytes().all(|b| b.is_ascii_digit());
+    let unsigned = raw.strip_prefix(['-', '+']).unwrap_or(raw);
+    let (mantissa, exponent) = match unsigned.split_once(['e', 'E']) {
+        Some((m, e)) => (m, Some(e.strip_prefix(['-', '+']).unwrap_or(e))),
+        None => (unsigned, None),
+    };
+    let (integer, fraction) = match mantissa.split_once('.') {
+        Some((i, f)) => (i, Some(f)),
+        None => (mantissa, None),
+    };
+    digits(integer)
+    && (integer == "0" || !integer.starts_with('0'))
+    && (fraction.is_some() || exponent.is_some())
+    && fraction.is_none_or(digits)
+    && exponent.is_none_or(digits)
+}
+
+impl Display for Value {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Value::Null => write!(f, "NULL"),
+            Value::Integer(i) => write!(f, "{}", i),
+            // Floats keep a decimal point, so that they are read back as floats
+            Value::Float(n) if n.is_finite() && n.fract() == 0.0 => write!(f, "{:.1}", n),
+            Value::Float(n) => write!(f, "{}", n),
+            Value::Decimal(_, s) => write!(f, "{}", s),
+            Value::Boolean(b) => write!(f, "{}", b),
+            Value::Text(s) => write!(f, "{}", s),
+            Value::Date(d) => write!(f, "{}", d.format(DATE_FORMAT)),
+            Value::Timestamp(ts) => write!(f, "{}", ts.format(TIMESTAMP_FORMAT)),
+            Value::Interval(i) => write!(f, "{}", i),
+        }
+    }
+}
+
+impl From<&str> for Value {
+    fn from(value: &str) -> Self {
+        Value::Text(value.to_string())
+    }
+}
+
+impl From<i64> for Value {
+    fn from(value: i64) -> Self {
+        Value::Integer(value)
+    }
+}
+
+impl From<f64> for Value {
+    fn from(value: f64) -> Self {
+        Value::Float(value)
+    }
+}
+
+impl From<bool> for Value {
+    fn from(value: bool) -> Self {
+        Value::Boolean(value)
+    }
+}
+
+/// Total order used to sort and group values : NULL first, then booleans, numbers, dates and texts.
+/// Integers and floats are compared numerically, dates being considered at midnight when compared to timestamps.
+impl Ord for Value {
+    fn cmp(&self, other: &Self) -> Ordering {
+        match (self, other) {
+            (Value::Decimal(f, _), o) => Value::Float(*f).cmp(o),
+            (v, Value::Decimal(f, _)) => v.cmp(&Value::Float(*f)),
+            (Value::Null, Value::Null) => Ordering::Equal,
+            (Value::Boolean(b1), Value::Boolean(b2)) => b1.cmp(b2),
+            (Value::Integer(i1), Value::Integer(i2)) => i1.cmp(i2),
+            (Value::Integer(i), Value::Float(f)) => compare_floats(*i as f64, *f),
+            (Value::Float(f), Value::Integer(i)) => compare_floats(*f, *i as f64),
+            (Value::Float(f1), Value::Float(f2)) => compare_floats(*f1, *f2),
+            (Value::Date(d1), Value::Date(d2)) => d1.cmp(d2),
+            (Value::Timestamp(ts1), Value::Timestamp(ts2)) => ts1.cmp(ts2),
+            (Value::Date(d), Value::Timestamp(ts)) => d.and_time(Default::default()).cmp(ts),
+            (Value::Timestamp(ts), Value::Date(d)) => ts.cmp(&d.and_time(Default::default())),
+            (Value::Interval(i1), Value::Interval(i2)) => i1.cmp(i2),
+            (Value::Text(s1), Value::Text(s2)) => s1.cmp(s2),
+            _ => self.type_rank().cmp(&other.type_rank()),
+        }
+    }
+}
+
+/// Numerical order of two floats, `-0.0` being equal to `0.0`, and NaN coming after every other number
+fn compare_floats(f1: f64, f2: f64) -> Ordering {
+    f1.partial_cmp(&f2).unwrap_or_else(|| f1.total_cmp(&f2))
+}
+
+impl PartialOrd for Value {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl PartialEq for Value {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for Value {}
+
+impl Hash for Value {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.type_rank().hash(state);
+        match self {
+            Value::Null => (),
+            Value::Boolean(b) => b.hash(state),
+            // Equal integers and floats must share the same hash
+            Value::Integer(i) => (*i as f64).to_bits().hash(state),
+            Value::Float(f) | Value::Decimal(f, _) if *f == 0.0 => 0f64.to_bits().hash(state),
+            Value::Float(f) | Value::Decimal(f, _) => f.to_bits().hash(state),
+            Value::Date(d) => d.and_time(Default::default()).hash(state),
+            Value::Timestamp(ts) => ts.hash(state),
+            Value::Interval(i) => i.hash(state),
+            Value::Text(s) => s.hash(state),
+        }
+    }
+}
+
+/// Amount of time added to dates and timestamps.
+/// Months and days are kept apart from seconds, since their length varies.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Interval {
+    pub months: i32,
+    pub days: i32,
+    pub seconds: i64,
+}
+
+impl Interval {
+    const UNITS: [(&'static str, &'static str); 7] = [
+        ("year", "years"),
+        ("month", "months"),
+        ("week", "weeks"),
+        ("day", "days"),
+        ("hour", "hours"),
+        ("minute", "minutes"),
+        ("second", "seconds"),
+    ];
+
+    /// Interval written as amounts of units, such as `1 year 2 months` or `-3 days`
+    pub fn parse(text: &str) -> Option<Self> {
+        let words: Vec<String> = text.split_whitespace().map(|w| w.to_lowercase()).collect();
+        if words.is_empty() || !words.len().is_multiple_of(2) {
+            return None;
+        }
+        let mut res = Interval::default();
+        for pair in words.chunks(2) {
+            let amount = pair[0].parse::<i32>().ok()?;
+            let unit = match pair[1].as_str() {
+                "mon" | "mons" => "month",
+                "min" | "mins" => "minute",
+                "sec" | "secs" => "second",
+                u => Self::UNITS.iter().find(|(one, many)| u == *one || u == *many)?.0,
+            };
+            res = res.add(&Self::of(amount, unit)?)?;
+        }
+        Some(res)
+    }
+
+    /// `amount` times the unit, named in the singular
+    pub fn of(amount: i32, unit: &str) -> Option<Self> {
+        let amount64 = amount as i64;
+        match unit {
+            "year" => Some(Interval { months: amount.checked_mul(12)?, ..Default::default() }),
+            "month" => Some(Interval { months: amount, ..Default::default() }),
+            "week" => Some(Interval { days: amount.checked_mul(7)?, ..Default::default() }),
+            "day" => Some(Interval { days: amount, ..Default::default() }),
+            "hour" => Some(Interval { seconds: amount64 * 3600, ..Default::default() }),
+            "minute" => Some(Interval { seconds: amount64 * 60, ..Default::default() }),
+            "second" => Some(Interval { seconds: amount64, ..Default::default() }),
+            _ => None,
+        }
+    }
+
+    pub fn add(&self, other: &Self) -> Option<Self> {
+        Some(Interval {
+            months: self.months.checked_add(other.months)?,
+            days: self.days.checked_add(other.days)?,
+            seconds: self.seconds.checked_add(other.seconds)?,
+        })
+    }
+
+    pub fn negated(&self) -> Self {
+        Interval { months: -self.months, days: -self.days, seconds: -self.seconds }
+    }
+
+    /// Length of the interval in seconds, months being counted as 30 days as in PostgreSQL
+    pub fn approximate_seconds(&self) -> i128 {
+        (self.months as i128 * 30 + self.days as i128) * 86400 + self.seconds as i128
+    }
+}
+
+impl Display for Interval {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let amounts = [
+            self.months as i64 / 12,
+            self.months as i64 % 12,
+            0,
+            self.days as i64,
+            self.seconds / 3600,
+            self.seconds % 3600 / 60,
+            self.seconds % 60,
+        ];
+        let parts: Vec<String> = amounts.iter()
+        .zip(Self::UNITS.iter())
+        .filter(|(amount, _)| **amount != 0)
+        .map(|(amount, (one, many))| format!("{} {}", amount, if amount.abs() == 1 { one } else { many }))
+        .collect();
+        if parts.is_empty() {
+            write!(f, "0 seconds")
+        } else {
+            write!(f, "{}", parts.join(" "))
+        }
+    }
+}
+
+/// Intervals are equal when they last as long, one month being as long as 30 days
+impl Ord for Interval {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.approximate_seconds().cmp(&other.approximate_seconds())
+    }
+}
+
+impl PartialOrd for Interval {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl PartialEq for Interval {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for Interval {}
+
+impl Hash for Interval {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.approximate_seconds().hash(state);
+    }
+}
@@ -1,4 +1,6 @@
-use csql::traits::Executable;
+use csql::commands::Command;
+use csql::traits::{ComparisonOperator, Condition, Executable, Expression, Frame, FrameBound, WindowFunction};
+use csql::utils::Value;
 use sqlparser::dialect::GenericDialect;
 use sqlparser::parser::{Parser, ParserError};
 
@@ -79,4 +81,120 @@ fn parsing_delete() -> Result<(), ParserError> {
         println!("{:?}", commands);
     }
     Ok(())
-}
\ No newline at end of file
+}
+
+/// Condition of the WHERE clause of `select id from user where <clause>`
+fn where_condition(clause: &str) -> Condition {
+    let sql = format!("select id from user where {}", clause);
+    let statements = Parser::parse_sql(&GenericDialect {}, &sql).unwrap();
+    match statements[0].deserialize_as_command() {
+        Ok(Command::Select { conditions: Ok(Some(cond)), .. }) => cond,
+        other => panic!("No condition deserialized for {} : {:?}", sql, other),
+    }
+}
+
+#[test]
+fn comparison_normalization_test() {
+    let operators = [
+        ("=", ComparisonOperator::Equal, ComparisonOperator::Equal),
+        ("<>", ComparisonOperator::NotEqual, ComparisonOperator::NotEqual),
+        ("!=", ComparisonOperator::NotEqual, ComparisonOperator::NotEqual),
+        (">", ComparisonOperator::GreaterThan, ComparisonOperator::LessThan),
+        (">=", ComparisonOperator::GreaterOrEqual, ComparisonOperator::LessOrEqual),
+        ("<", ComparisonOperator::LessThan, ComparisonOperator::GreaterThan),
+        ("<=", ComparisonOperator::LessOrEqual, ComparisonOperator::GreaterOrEqual),
+    ];
+    let literals = [
+        ("5", Value::Integer(5)),
+        ("-5", Value::Integer(-5)),
+        ("2.5", Value::Float(2.5)),
+        ("'5'", Value::from("5")),
+        ("true", Value::Boolean(true)),
+        ("null", Value::Null),
+    ];
+    let id = Expression::Column(String::from("id"));
+    for (sql_op, op, mirrored) in operators.iter() {
+        for (sql_lit, lit) in literals.iter() {
+            let expected = [
+                (format!("id {} {}", sql_op, sql_lit), op),
+                (format!("{} {} id", sql_lit, sql_op), mirrored),
+                (format!("({}) {} (id)", sql_lit, sql_op), mirrored),
+            ];
+            for (clause, expected_op) in expected.iter() {
+                match where_condition(clause) {
+                    Condition::Compare(left, op, right) => {
+                        assert_eq!(left, id, "{}", clause);
+                        assert_eq!(&op, *expected_op, "{}", clause);
+                        assert_eq!(right, Expression::Literal(lit.clone()), "{}", clause);
+                    },
+                    other => panic!("{} deserialized as {:?}", clause, other),
+                }
+            }
+        }
+        let operands = [
+            (format!("id {} author_id", sql_op), Expression::Column(String::from("id")), Expression::Column(String::from("author_id"))),
+            (format!("1 {} 2", sql_op), Expression::Literal(Value::Integer(1)), Expression::Literal(Value::Integer(2))),
+        ];
+        for (clause, left, right) in operands.iter() {
+            match where_condition(clause) {
+                Condition::Compare(l, o, r) => assert_eq!((&l, &o, &r), (left, op, right), "{}", clause),
+                other => panic!("{} deserialized as {:?}", clause, other),
+            }
+        }
+    }
+}
+
+#[test]
+fn case_deserialization_test() {
+    let simple = where_condition("case author_id when 1 then 'one' else 'other' end = 'one'");
+    let searched = where_condition("case when 1 = author_id then 'one' else 'other' end = 'one'");
+    assert_eq!(simple, searched);
+    match simple {
+        Condition::Compare(Expression::Case { branches, otherwise }, ComparisonOperator::Equal, _) => {
+            assert_eq!(branches, vec![(
+                Condition::column_compared("author_id", ComparisonOperator::Equal, Value::Integer(1)),
+                Expression::Literal(Value::from("one")),
+            )]);
+            assert_eq!(otherwise, Some(Box::new(Expression::Literal(Value::from("other")))));
+        },
+        other => panic!("CASE deserialized as {:?}", other),
+    }
+}
+
+#[test]
+fn subquery_deserialization_test() {
+    match where_condition("id not in (select author_id from post)") {
+        Condition::Not(cond) => match *cond {
+            Condition::InSubquery(expr, query) => {
+                assert_eq!(expr, Expression::Column(String::from("id")));
+                assert_eq!(query.sql, "SELECT author_id FROM post");
+                assert!(matches!(*query.command, Command::Select { .. }));
+            },
+            other => panic!("NOT IN deserialized as {:?}", other),
+        },
+        other => panic!("NOT IN deserialized as {:?}", other),
+    }
+    assert!(matches!(where_condition("exists (select 1 from post)"), Condition::Exists(_)));
+    assert!(matches!(
+        where_condition("(select count(*) from post) > 2"),
+        Condition::Compare(Expression::Subquery(_), ComparisonOperator::GreaterThan, _),
+    ));
+}
+
+#[test]
+fn window_deserialization_test() {
+    let sql = "select lag(title, 2) over (partition by author_id order by created rows between 2 preceding and 1 following),
+    rank() over (order by id), rank() over (order by id) + 1 from post";
+    let statements = Parser::parse_sql(&GenericDialect {}, sql).unwrap();
+    match statements[0].deserialize_as_command() {
+        Ok(Command::Select { windows, .. }) => {
+            assert_eq!(windows.len(), 2);
+            assert!(matches!(&windows[0].function, WindowFunction::Lag { offset: 2, default: Expression::Literal(Value::Null), .. }));
+            assert_eq!(windows[0].partition_by, vec![Expression::Column(String::from("author_id"))]);
+            assert_eq!(windows[0].frame, Frame { rows: true, start: FrameBound::Preceding(2), end: FrameBound::Following(1) });
+            assert!(matches!(windows[1].function, WindowFunction::Rank));
+            assert_eq!(windows[1].frame, Frame { rows: false, start: FrameBound::UnboundedPreceding, end: FrameBound::CurrentRow });
+        },
+        other => panic!("Windows not deserialized for {} : {:?}", sql, other),
+    }
+}
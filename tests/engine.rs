@@ -0,0 +1,1100 @@
+use std::{fs, path::PathBuf};
+
+use csql::engine::{QueryResult, Session};
+use csql::entities::{DataStore, Table};
+use csql::errors::{ExecutionError, TableInitError};
+use csql::traits::{InsertElement, Queryable};
+use csql::utils::Value;
+
+fn sample_session() -> Result<Session, TableInitError> {
+    let mut t = Table::new("user", &vec!["id", "username", "password"])?;
+    let lines = vec![
+        vec![
+            Value::Integer(1),
+            Value::Text(String::from("john.doe123")),
+            Value::Text(String::from("abcd1234")),
+        ],
+        vec![
+            Value::Integer(2),
+            Value::Text(String::from("jrogan_$89")),
+            Value::Text(String::from("zzz")),
+        ],
+    ];
+    for l in lines {
+        t.insert(&None, InsertElement::PlainValues(l))
+        .map_err(|_| TableInitError::new("Error while inserting element..."))?;
+    }
+    Ok(Session::new(DataStore::new(vec![t])))
+}
+
+/// Copy of a data directory, removed once dropped, for the tests whose statements modify the files
+struct ScratchDir(PathBuf);
+
+impl ScratchDir {
+    fn copy_of(dir: &str, test: &str) -> Self {
+        let path = std::env::temp_dir().join(format!("csql_{}_{}", test, std::process::id()));
+        fs::create_dir_all(&path).unwrap();
+        for e in fs::read_dir(dir).unwrap() {
+            let e = e.unwrap();
+            fs::copy(e.path(), path.join(e.file_name())).unwrap();
+        }
+        Self(path)
+    }
+
+    fn session(&self) -> Result<Session, ExecutionError> {
+        Session::from_directory(self.0.to_str().unwrap())
+    }
+}
+
+impl Drop for ScratchDir {
+    fn drop(&mut self) {
+        let _ = fs::remove_dir_all(&self.0);
+    }
+}
+
+#[test]
+fn select_statement_test() -> Result<(), TableInitError> {
+    let mut session = sample_session()?;
+    let res = session.execute("select password, id from user where id = 2").unwrap();
+    assert_eq!(res, vec![QueryResult::Rows {
+        headers: vec![String::from("password"), String::from("id")],
+        rows: vec![vec![Value::Text(String::from("zzz")), Value::Integer(2)]],
+    }]);
+    Ok(())
+}
+
+#[test]
+fn modifying_statements_test() -> Result<(), TableInitError> {
+    let mut session = sample_session()?;
+    let res = session.execute("
+    insert into user values (3, 'mickael76', 'okokokok');
+    update user set password = 'changed' where id > 1;
+    delete from user where username = 'john.doe123';
+    select * from user").unwrap();
+    assert_eq!(res, vec![
+        QueryResult::AffectedRows(1),
+        QueryResult::AffectedRows(2),
+        QueryResult::AffectedRows(1),
+        QueryResult::Rows {
+            headers: vec![String::from("id"), String::from("username"), String::from("password")],
+            rows: vec![
+                vec![Value::Integer(2), Value::Text(String::from("jrogan_$89")), Value::Text(String::from("changed"))],
+                vec![Value::Integer(3), Value::Text(String::from("mickael76")), Value::Text(String::from("changed"))],
+            ],
+        },
+    ]);
+    Ok(())
+}
+
+#[test]
+fn failing_statements_test() -> Result<(), TableInitError> {
+    let mut session = sample_session()?;
+    assert!(matches!(session.execute("selec * from user"), Err(ExecutionError::Parsing(_))));
+    assert!(matches!(session.execute("select * from unknown"), Err(ExecutionError::Querying(_))));
+    Ok(())
+}
+
+#[test]
+fn query_error_reasons_test() -> Result<(), ExecutionError> {
+    let mut session = Session::from_directory("./fake_data/")?;
+    for (sql, reason) in [
+        ("select * from unknown", "Unknown table unknown"),
+        ("select nickname from user", "Unknown column nickname"),
+        ("select id from user u join post p on p.author_id = u.id", "Ambiguous column id"),
+        ("select 1 / 0", "Division by zero"),
+        ("insert into post (id, author_id) values (1, 1)", "Duplicate primary key (1)"),
+        ("insert into post (id, title) values (8, 'No author')", "The column author_id cannot be NULL."),
+        ("insert into post (id, author_id, nickname) values (8, 1, 'x')", "Unknown column nickname"),
+        ("insert into post (id, author_id, nickname) select id + 10, author_id, title from post", "Unknown column nickname"),
+        ("insert into post (id, author_id) select id + 10 from post", "2 columns listed, but the query returns 1 columns"),
+    ] {
+        match session.execute(sql) {
+            Err(ExecutionError::Querying(e)) => assert_eq!(e.reason(), reason, "{}", sql),
+            other => panic!("{} gave {:?}", sql, other),
+        }
+    }
+    Ok(())
+}
+
+#[test]
+fn session_from_directory_test() -> Result<(), ExecutionError> {
+    let mut session = Session::from_directory("./samples/")?;
+    let res = session.execute("select c, a from example0 where a = 'x'")?;
+    assert_eq!(res, vec![QueryResult::Rows {
+        headers: vec![String::from("c"), String::from("a")],
+        rows: vec![vec![Value::Text(String::from("z")), Value::Text(String::from("x"))]],
+    }]);
+    Ok(())
+}
+
+#[test]
+fn result_display_test() {
+    let rows = QueryResult::Rows {
+        headers: vec![String::from("id"), String::from("username")],
+        rows: vec![
+            vec![Value::Integer(1), Value::Text(String::from("john.doe123"))],
+            vec![Value::Integer(20), Value::Null],
+        ],
+    };
+    let expected = " id | username    \n----+-------------\n 1  | john.doe123 \n 20 | NULL        \n(2 rows)";
+    assert_eq!(rows.to_string(), expected);
+    assert_eq!(QueryResult::AffectedRows(1).to_string(), "1 row affected");
+}
+
+fn column_values(result: &QueryResult, column: usize) -> Vec<Value> {
+    match result {
+        QueryResult::Rows { rows, .. } => rows.iter().map(|r| r[column].clone()).collect(),
+        QueryResult::AffectedRows(_) => vec![],
+    }
+}
+
+fn values(raw: &[&str]) -> Vec<Value> {
+    raw.iter().map(|v| Value::infer(v)).collect()
+}
+
+#[test]
+fn inner_join_test() -> Result<(), ExecutionError> {
+    let mut session = Session::from_directory("./fake_data/")?;
+    let res = session.execute("
+    select u.firstname, p.title from user u join post p on u.id = p.author_id where u.firstname = 'John';
+    select firstname from following f inner join user u on f.followed_id = u.id where f.follower_id = '2';
+    select firstname from post, user u where author_id = u.id and title = 'Training'")?;
+    match &res[0] {
+        QueryResult::Rows { headers, .. } => assert_eq!(headers, &vec![String::from("firstname"), String::from("title")]),
+        QueryResult::AffectedRows(_) => panic!("SELECT should return rows"),
+    }
+    assert_eq!(column_values(&res[0], 1), values(&["Hello world", "Back again"]));
+    assert_eq!(column_values(&res[1], 0), values(&["John", "Mickael"]));
+    assert_eq!(column_values(&res[2], 0), values(&["Mickael"]));
+    Ok(())
+}
+
+#[test]
+fn outer_join_test() -> Result<(), ExecutionError> {
+    let mut session = Session::from_directory("./fake_data/")?;
+    let res = session.execute("
+    select u.firstname, p.id from user u left join post p on u.id = p.author_id;
+    select u.firstname, p.title from user u right outer join post p on u.id = p.author_id;
+    select u.id, p.id from user u full join post p on u.id = p.author_id")?;
+    assert_eq!(column_values(&res[0], 0), values(&["John", "John", "Jane", "Mickael", "Mickael", "Joe", "Alice"]));
+    assert_eq!(column_values(&res[0], 1)[5..], [Value::Null, Value::Null]);
+    assert_eq!(column_values(&res[1], 0).len(), 6);
+    assert_eq!(column_values(&res[1], 0)[5], Value::Null);
+    assert_eq!(column_values(&res[2], 0).len(), 8);
+    assert_eq!(column_values(&res[2], 0)[7], Value::Null);
+    assert_eq!(column_values(&res[2], 1)[7], Value::Integer(6));
+    Ok(())
+}
+
+#[test]
+fn cross_and_using_join_test() -> Result<(), ExecutionError> {
+    let mut session = Session::from_directory("./fake_data/")?;
+    let res = session.execute("
+    select * from user cross join following;
+    select * from user, post;
+    select id, lastname from post join user using (id) where title = 'Training'")?;
+    assert_eq!(column_values(&res[0], 0).len(), 30);
+    assert_eq!(column_values(&res[1], 0).len(), 30);
+    match &res[0] {
+        QueryResult::Rows { headers, .. } => assert_eq!(headers.len(), 7),
+        QueryResult::AffectedRows(_) => panic!("SELECT should return rows"),
+    }
+    assert_eq!(column_values(&res[2], 1), values(&["Martin"]));
+    assert!(session.execute("select id from user, post").is_err());
+    Ok(())
+}
+
+#[test]
+fn aggregates_test() -> Result<(), ExecutionError> {
+    let mut session = Session::from_directory("./fake_data/")?;
+    let res = session.execute("
+    select count(*), count(distinct author_id), sum(author_id), avg(id), min(created), max(title) from post;
+    select count(id), sum(id) from post where id = 42")?;
+    assert_eq!(res[0], QueryResult::Rows {
+        headers: ["count(*)", "count(DISTINCT author_id)", "sum(author_id)", "avg(id)", "min(created)", "max(title)"]
+        .iter()
+        .map(|h| h.to_string())
+        .collect(),
+        rows: vec![values(&["6", "4", "19", "3.5", "2021-01-01", "Training"])],
+    });
+    assert_eq!(res[1], QueryResult::Rows {
+        headers: vec![String::from("count(id)"), String::from("sum(id)")],
+        rows: vec![vec![Value::Integer(0), Value::Null]],
+    });
+    Ok(())
+}
+
+#[test]
+fn group_by_having_test() -> Result<(), ExecutionError> {
+    let mut session = Session::from_directory("./fake_data/")?;
+    let res = session.execute("
+    select author_id, count(*) from post group by author_id;
+    select u.firstname, count(p.id) from user u left join post p on u.id = p.author_id group by u.id, u.firstname having count(p.id) = 0;
+    select 'many' from post having count(*) > 5;
+    select 'posts' from post having 1 = 1;
+    select author_id from post group by author_id order by COUNT(*) desc, author_id;
+    select author_id, count(*) from post group by author_id having COUNT(*) > 1 order by Count(*), author_id desc")?;
+    assert_eq!(column_values(&res[0], 0), values(&["1", "2", "3", "9"]));
+    assert_eq!(column_values(&res[0], 1), values(&["2", "1", "2", "1"]));
+    assert_eq!(column_values(&res[1], 0), values(&["Joe", "Alice"]));
+    // Without GROUP BY, HAVING filters the single group of all the records
+    assert_eq!(column_values(&res[2], 0), values(&["many"]));
+    assert_eq!(column_values(&res[3], 0), values(&["posts"]));
+    // Aggregates may only sort the groups, their names being written in any case
+    assert_eq!(column_values(&res[4], 0), values(&["1", "3", "2", "9"]));
+    assert_eq!(column_values(&res[5], 0), values(&["3", "1"]));
+    assert!(session.execute("select soundex(title) from post").is_err());
+    // Columns read once per group are grouping keys or aggregated
+    for sql in [
+        "select title, count(*) from post",
+        "select id from post having id > 4",
+        "select author_id, title from post group by author_id",
+        "select author_id from post group by author_id order by created",
+        "select author_id, count(*) from post group by author_id having max(id) > id",
+    ] {
+        match session.execute(sql) {
+            Err(ExecutionError::Querying(e)) => assert!(e.reason().contains("must appear in GROUP BY"), "{}", sql),
+            res => panic!("{} : {:?}", sql, res),
+        }
+    }
+    let res = session.execute("select post.author_id, count(*) + author_id from post group by author_id order by 1")?;
+    assert_eq!(column_values(&res[0], 1), values(&["3", "3", "5", "10"]));
+    Ok(())
+}
+
+#[test]
+fn group_by_expressions_test() -> Result<(), ExecutionError> {
+    let mut session = Session::from_directory("./samples/prices/")?;
+    let res = session.execute("
+    select case when price > 5 then 'high' else 'low' end as bucket, count(*), sum(price) from price group by bucket order by bucket;
+    select case when price > 5 then 'high' else 'low' end, count(*) from price group by case when price > 5 then 'high' else 'low' end;
+    select id % 2 as parity, max(product) from price group by 1 order by parity;
+    select upper(substr(product, 1, 1)) || '.' as initial from price group by substr(product, 1, 1) || '.', upper(substr(product, 1, 1)) order by 1")?;
+    assert_eq!(res[0], QueryResult::Rows {
+        headers: vec![String::from("bucket"), String::from("count(*)"), String::from("sum(price)")],
+        rows: vec![
+            vec![Value::from("high"), Value::Integer(2), Value::Float(19.5)],
+            vec![Value::from("low"), Value::Integer(2), Value::Float(3.5)],
+        ],
+    });
+    assert_eq!(column_values(&res[1], 1), values(&["2", "2"]));
+    assert_eq!(res[2], QueryResult::Rows {
+        headers: vec![String::from("parity"), String::from("max(product)")],
+        rows: vec![
+            vec![Value::Integer(0), Value::from("Mug")],
+            vec![Value::Integer(1), Value::from("Pen")],
+        ],
+    });
+    assert_eq!(column_values(&res[3], 0), values(&["C.", "L.", "M.", "P."]));
+    assert!(session.execute("select product, count(*) from price group by id % 2").is_err());
+    assert!(session.execute("select id from price group by 2").is_err());
+    Ok(())
+}
+
+#[test]
+fn aggregate_expressions_test() -> Result<(), ExecutionError> {
+    let mut session = Session::from_directory("./samples/prices/")?;
+    let res = session.execute("
+    select sum(price * id), max(length(product)), count(distinct id % 2), min(case when price > 5 then product end) from price;
+    select sum(price * 2) over (order by id) from price order by id")?;
+    assert_eq!(res[0], QueryResult::Rows {
+        headers: vec![
+            String::from("sum(price * id)"),
+            String::from("max(length(product))"),
+            String::from("count(DISTINCT id % 2)"),
+            String::from("min(CASE WHEN price > 5 THEN product END)"),
+        ],
+        rows: vec![vec![Value::Float(41.0), Value::Integer(5), Value::Integer(2), Value::from("Chair")]],
+    });
+    assert_eq!(column_values(&res[1], 0), vec![20.0, 39.0, 43.0, 46.0].into_iter().map(Value::Float).collect::<Vec<Value>>());
+    Ok(())
+}
+
+#[test]
+fn order_by_test() -> Result<(), ExecutionError> {
+    let mut t = Table::new("scores", &vec!["name", "score"]).unwrap();
+    for (name, score) in [("a", Some(9)), ("b", Some(10)), ("c", None), ("d", Some(2)), ("e", Some(10))] {
+        t.insert(&None, InsertElement::PlainValues(vec![Value::from(name), score.map(Value::Integer).unwrap_or(Value::Null)]))?;
+    }
+    let mut session = Session::new(DataStore::new(vec![t]));
+    let res = session.execute("
+    select name from scores order by score;
+    select name from scores order by score desc, name desc;
+    select name from scores order by score nulls first;
+    select name, score from scores order by 2 desc nulls last, 1")?;
+    assert_eq!(column_values(&res[0], 0), values(&["d", "a", "b", "e", "c"]));
+    assert_eq!(column_values(&res[1], 0), values(&["c", "e", "b", "a", "d"]));
+    assert_eq!(column_values(&res[2], 0), values(&["c", "d", "a", "b", "e"]));
+    assert_eq!(column_values(&res[3], 0), values(&["b", "e", "a", "d", "c"]));
+    Ok(())
+}
+
+#[test]
+fn limit_offset_test() -> Result<(), ExecutionError> {
+    let mut session = Session::from_directory("./fake_data/")?;
+    let res = session.execute("
+    select id from post order by id desc limit 2;
+    select id from post order by id limit 2 offset 3;
+    select id from post order by id offset 4 rows;
+    select id from post order by id fetch first 3 rows only;
+    select id from post limit 0")?;
+    assert_eq!(column_values(&res[0], 0), values(&["6", "5"]));
+    assert_eq!(column_values(&res[1], 0), values(&["4", "5"]));
+    assert_eq!(column_values(&res[2], 0), values(&["5", "6"]));
+    assert_eq!(column_values(&res[3], 0), values(&["1", "2", "3"]));
+    assert_eq!(column_values(&res[4], 0), values(&[]));
+    Ok(())
+}
+
+#[test]
+fn decimal_values_test() -> Result<(), ExecutionError> {
+    let mut session = Session::from_directory("./samples/prices/")?;
+    let res = session.execute("
+    select product from price order by price;
+    select max(price), min(price), sum(price) from price;
+    select product from price where price > 9.6")?;
+    assert_eq!(column_values(&res[0], 0), values(&["Mug", "Pen", "Lamp", "Chair"]));
+    assert_eq!(res[1], QueryResult::Rows {
+        headers: vec![String::from("max(price)"), String::from("min(price)"), String::from("sum(price)")],
+        rows: vec![vec![Value::Float(10.0), Value::Float(1.5), Value::Float(23.0)]],
+    });
+    if let QueryResult::Rows { rows, .. } = &res[1] {
+        assert_eq!(rows[0].iter().map(Value::to_string).collect::<Vec<String>>(), vec!["10.00", "1.5", "23.0"]);
+    }
+    assert_eq!(column_values(&res[2], 0), values(&["Chair"]));
+    Ok(())
+}
+
+#[test]
+fn session_persistence_test() -> Result<(), ExecutionError> {
+    let dir = ScratchDir::copy_of("./fake_data/", "session_persistence");
+    let mut session = dir.session()?;
+    session.execute("
+    insert into post (id, author_id, title, created) values (7, 1, 'Saved', '2023-07-01');
+    update user set firstname = 'Renamed' where id = 1;
+    delete from post where id = 2")?;
+    // A failing statement leaves the files as they are
+    assert!(session.execute("insert into post (id, author_id) values (1, 1)").is_err());
+    let mut reloaded = dir.session()?;
+    let res = reloaded.execute("
+    select id, title, created from post where id > 5 or id = 2;
+    select firstname from user where id = 1;
+    select count(*) from post")?;
+    assert_eq!(res[0], QueryResult::Rows {
+        headers: vec![String::from("id"), String::from("title"), String::from("created")],
+        rows: vec![
+            vec![Value::Integer(6), Value::infer("Lost post"), Value::infer("2021-01-01")],
+            vec![Value::Integer(7), Value::infer("Saved"), Value::infer("2023-07-01")],
+        ],
+    });
+    assert_eq!(column_values(&res[1], 0), values(&["Renamed"]));
+    assert_eq!(column_values(&res[2], 0), values(&["6"]));
+    assert_eq!(fs::read(dir.0.join("following.csv")).unwrap(), fs::read("./fake_data/following.csv").unwrap());
+    Ok(())
+}
+
+#[test]
+fn typed_comparisons_test() -> Result<(), ExecutionError> {
+    let mut session = Session::from_directory("./fake_data/")?;
+    let res = session.execute("
+    select id from post where author_id < 10;
+    select id from post where created > '2023-01-01';
+    select max(created), sum(author_id), avg(id) from post;
+    select id from post order by author_id desc, id limit 3;
+    select id from post where '2023-01-01' < created;
+    select id from post where 3 >= author_id;
+    select id from post where title > 5;
+    select id from post where id * -0.0 = 0 and id < 3")?;
+    assert_eq!(column_values(&res[0], 0), values(&["1", "2", "3", "4", "5", "6"]));
+    assert_eq!(column_values(&res[1], 0), values(&["2", "4", "5"]));
+    assert_eq!(res[2], QueryResult::Rows {
+        headers: vec![String::from("max(created)"), String::from("sum(author_id)"), String::from("avg(id)")],
+        rows: vec![vec![Value::infer("2023-06-12"), Value::Integer(19), Value::Float(3.5)]],
+    });
+    assert_eq!(column_values(&res[3], 0), values(&["6", "4", "5"]));
+    assert_eq!(column_values(&res[4], 0), values(&["2", "4", "5"]));
+    assert_eq!(column_values(&res[5], 0), values(&["1", "2", "3", "4", "5"]));
+    assert_eq!(column_values(&res[6], 0), values(&[]));
+    assert_eq!(column_values(&res[7], 0), values(&["1", "2"]));
+    Ok(())
+}
+
+#[test]
+fn schema_constraints_test() -> Result<(), ExecutionError> {
+    let dir = ScratchDir::copy_of("./fake_data/", "schema_constraints");
+    let mut session = dir.session()?;
+    let res = session.execute("
+    insert into post (id, author_id, created) values ('7', 2, '2023-07-01');
+    select id, title, content, created from post where id = 7")?;
+    assert_eq!(res[1], QueryResult::Rows {
+        headers: vec![String::from("id"), String::from("title"), String::from("content"), String::from("created")],
+        rows: vec![vec![Value::Integer(7), Value::infer("Untitled"), Value::Null, Value::infer("2023-07-01")]],
+    });
+    // Duplicate primary keys, NULL in a non-nullable column and wrongly typed values
+    assert!(session.execute("insert into post (id, author_id) values (1, 1)").is_err());
+    assert!(session.execute("insert into post (id, title) values (8, 'No author')").is_err());
+    assert!(session.execute("insert into post (id, author_id, created) values (8, 1, 'yesterday')").is_err());
+    assert!(session.execute("update post set id = 1 where id = 2").is_err());
+    let res = session.execute("select count(*) from post")?;
+    assert_eq!(column_values(&res[0], 0), values(&["7"]));
+    Ok(())
+}
+
+#[test]
+fn typed_columns_test() -> Result<(), ExecutionError> {
+    let dir = ScratchDir::copy_of("./samples/typed_columns/", "typed_columns");
+    let original = fs::read_to_string(dir.0.join("measure.csv")).unwrap();
+    let mut session = dir.session()?;
+    let res = session.execute("
+    select code, amount, day, note from measure order by id;
+    select id from measure order by amount;
+    select id from measure where day < '2023-02-01' and code = '2E3'")?;
+    assert_eq!(res[0], QueryResult::Rows {
+        headers: vec![String::from("code"), String::from("amount"), String::from("day"), String::from("note")],
+        rows: vec![
+            vec![Value::from("1.50"), Value::infer("1.50"), Value::infer("2023-02-14"), Value::from("03/01/2023")],
+            vec![Value::from("2E3"), Value::infer("2E3"), Value::infer("2023-01-03"), Value::from("2023-01-03")],
+            vec![Value::from("0.10"), Value::infer("0.10"), Value::infer("2022-12-25"), Value::Null],
+        ],
+    });
+    if let QueryResult::Rows { rows, .. } = &res[0] {
+        assert!(rows.iter().all(|r| matches!(r[0], Value::Text(_))));
+        assert_eq!(rows.iter().map(|r| r[1].to_string()).collect::<Vec<String>>(), vec!["1.50", "2E3", "0.10"]);
+    }
+    assert_eq!(column_values(&res[1], 0), values(&["3", "1", "2"]));
+    assert_eq!(column_values(&res[2], 0), values(&["2"]));
+    // Committing the table leaves the fields untouched by the statements as they are written
+    session.execute("update measure set note = 'checked' where id = 3")?;
+    assert_eq!(
+        fs::read_to_string(dir.0.join("measure.csv")).unwrap(),
+        original.replace("0.10;25/12/2022;", "0.10;25/12/2022;checked"),
+    );
+    session.execute("insert into measure values (4, date '2023-07-01', 3, '2023-07-01', '1.0')")?;
+    assert!(fs::read_to_string(dir.0.join("measure.csv")).unwrap().ends_with("\n4;01/07/2023;3.0;01/07/2023;1.0\n"));
+    Ok(())
+}
+
+#[test]
+fn file_dialects_test() -> Result<(), ExecutionError> {
+    let mut session = Session::from_directory("./samples/dialects/")?;
+    let res = session.execute("
+    select name, score from scores order by id;
+    select col2 from colors where col1 = 2;
+    select label from products where price > 20;
+    select motto from mottos order by id;
+    select name from events where day > '2023-01-01' order by day;
+    select extract(hour from start) from events where start < date '2023-01-01'")?;
+    assert_eq!(res[0], QueryResult::Rows {
+        headers: vec![String::from("name"), String::from("score")],
+        rows: vec![
+            vec![Value::infer("Smith, Anna"), Value::Float(12.5)],
+            vec![Value::infer("Bob"), Value::Null],
+            vec![Value::infer("NULL"), Value::Integer(7)],
+        ],
+    });
+    assert_eq!(column_values(&res[1], 0), values(&["green"]));
+    assert_eq!(column_values(&res[2], 0), values(&["Chair", "Table"]));
+    assert_eq!(column_values(&res[3], 0), vec![Value::infer("It's \\ fine"), Value::Null]);
+    assert_eq!(column_values(&res[4], 0), values(&["Review", "Launch"]));
+    assert_eq!(column_values(&res[5], 0), values(&["20"]));
+    Ok(())
+}
+
+#[test]
+fn where_predicates_test() -> Result<(), ExecutionError> {
+    let mut t = Table::new("scores", &vec!["name", "score"]).unwrap();
+    for (name, score) in [("a", Some(9)), ("b", Some(10)), ("c", None), ("d", Some(-2)), ("e", Some(10))] {
+        t.insert(&None, InsertElement::PlainValues(vec![Value::from(name), score.map(Value::Integer).unwrap_or(Value::Null)]))?;
+    }
+    let mut session = Session::new(DataStore::new(vec![t]));
+    let res = session.execute("
+    select name from scores where score <> 10;
+    select name from scores where score >= 9 and score <= 10;
+    select name from scores where not (score > 0);
+    select name from scores where score in (9, -2);
+    select name from scores where score not in (9, -2);
+    select name from scores where score not in (9, null);
+    select name from scores where score between -5 and 9;
+    select name from scores where score not between -5 and 9;
+    select name from scores where score is null;
+    select name from scores where score is not null and (name = 'a' or name = 'e');
+    select name from scores where not (score = 10 or score is null)")?;
+    assert_eq!(column_values(&res[0], 0), values(&["a", "d"]));
+    assert_eq!(column_values(&res[1], 0), values(&["a", "b", "e"]));
+    assert_eq!(column_values(&res[2], 0), values(&["d"]));
+    assert_eq!(column_values(&res[3], 0), values(&["a", "d"]));
+    assert_eq!(column_values(&res[4], 0), values(&["b", "e"]));
+    // Comparing to NULL is never true, so that nothing is kept
+    assert_eq!(column_values(&res[5], 0), values(&[]));
+    assert_eq!(column_values(&res[6], 0), values(&["a", "d"]));
+    assert_eq!(column_values(&res[7], 0), values(&["b", "e"]));
+    assert_eq!(column_values(&res[8], 0), values(&["c"]));
+    assert_eq!(column_values(&res[9], 0), values(&["a", "e"]));
+    assert_eq!(column_values(&res[10], 0), values(&["a", "d"]));
+    Ok(())
+}
+
+#[test]
+fn pattern_matching_test() -> Result<(), ExecutionError> {
+    let dir = ScratchDir::copy_of("./fake_data/", "pattern_matching");
+    let mut session = dir.session()?;
+    let res = session.execute("
+    insert into post (id, author_id, title) values (7, 1, '100% sure');
+    select id from user where email like '%@gmail.com';
+    select id from user where email not like '%@gmail.com';
+    select id from user where firstname like 'J_e';
+    select id from user where lastname ilike 'DO%';
+    select id from user where email like '%example.com' and password like 'abcd%';
+    select id from post where title like '%!%%' escape '!';
+    select id from user where email ~ '^[a-z]+\\.[a-z]+@';
+    select id from user where firstname ~* '^j';
+    select id from user where firstname !~ 'o';
+    select id from user where regexp_like(email, 'podcast|example');
+    select id from user where email REGEXP 'podcast|example' and lastname <> 'not regexp';
+    select id from user where firstname not rlike 'o'")?;
+    assert_eq!(column_values(&res[1], 0), values(&["2", "5"]));
+    assert_eq!(column_values(&res[2], 0), values(&["1", "3", "4"]));
+    assert_eq!(column_values(&res[3], 0), values(&["4"]));
+    assert_eq!(column_values(&res[4], 0), values(&["1"]));
+    assert_eq!(column_values(&res[5], 0), values(&["1"]));
+    assert_eq!(column_values(&res[6], 0), values(&["7"]));
+    assert_eq!(column_values(&res[7], 0), values(&["1", "2", "5"]));
+    assert_eq!(column_values(&res[8], 0), values(&["1", "2", "4"]));
+    assert_eq!(column_values(&res[9], 0), values(&["2", "3", "5"]));
+    assert_eq!(column_values(&res[10], 0), values(&["1", "3", "4"]));
+    assert_eq!(column_values(&res[11], 0), values(&["1", "3", "4"]));
+    assert_eq!(column_values(&res[12], 0), values(&["2", "3", "5"]));
+    assert!(session.execute("select id from user where email ~ '('").is_err());
+    // The operators are words which may name columns as well
+    let mut t = Table::new("words", &vec!["id", "rlike", "regexp"]).unwrap();
+    for (id, word) in [(1, "apple"), (2, "banana"), (3, "avocado")] {
+        t.insert(&None, InsertElement::PlainValues(vec![Value::Integer(id), Value::from(word), Value::from("^a")]))?;
+    }
+    let mut session = Session::new(DataStore::new(vec![t]));
+    let res = session.execute("
+    select rlike, regexp from words where rlike rlike '^a' and rlike not regexp 'pl' order by rlike;
+    select id from words where rlike regexp 'an' and (rlike) = 'banana'")?;
+    assert_eq!(res[0], QueryResult::Rows {
+        headers: vec![String::from("rlike"), String::from("regexp")],
+        rows: vec![vec![Value::from("avocado"), Value::from("^a")]],
+    });
+    assert_eq!(column_values(&res[1], 0), values(&["2"]));
+    Ok(())
+}
+
+#[test]
+fn expression_conditions_test() -> Result<(), ExecutionError> {
+    let dir = ScratchDir::copy_of("./fake_data/", "expression_conditions");
+    let mut session = dir.session()?;
+    let res = session.execute("
+    insert into following values (5, 5);
+    select follower_id from following where follower_id = followed_id;
+    select followed_id from following where follower_id < followed_id;
+    select id from post where id * 2 > author_id + 3;
+    select id from post where -author_id > -2;
+    select id from user where 3 < id;
+    select id from user where '2' >= id;
+    select id from user where length(firstname) = 4;
+    select id from user where upper(lastname) = 'DOE';
+    select id from user where firstname || ' ' || lastname = 'Joe Rogan'")?;
+    assert_eq!(column_values(&res[1], 0), values(&["5"]));
+    assert_eq!(column_values(&res[2], 0), values(&["2", "3", "4", "5", "3"]));
+    assert_eq!(column_values(&res[3], 0), values(&["3", "4", "5"]));
+    assert_eq!(column_values(&res[4], 0), values(&["1", "2"]));
+    assert_eq!(column_values(&res[5], 0), values(&["4", "5"]));
+    assert_eq!(column_values(&res[6], 0), values(&["1", "2"]));
+    assert_eq!(column_values(&res[7], 0), values(&["1", "2"]));
+    assert_eq!(column_values(&res[8], 0), values(&["1"]));
+    assert_eq!(column_values(&res[9], 0), values(&["4"]));
+    assert!(session.execute("select id from user where id / 0 = 1").is_err());
+    Ok(())
+}
+
+#[test]
+fn boolean_expressions_test() -> Result<(), ExecutionError> {
+    let mut t = Table::new("task", &vec!["id", "done", "a", "b"]).unwrap();
+    for (id, done, a, b) in [(1, Value::Boolean(true), 1, 2), (2, Value::Boolean(false), 5, 3), (3, Value::Null, 4, 4)] {
+        t.insert(&None, InsertElement::PlainValues(vec![Value::Integer(id), done, Value::Integer(a), Value::Integer(b)]))?;
+    }
+    let mut session = Session::new(DataStore::new(vec![t]));
+    let res = session.execute("
+    select id from task where done;
+    select id from task where not done;
+    select id from task where (a > b) = true;
+    select id from task where done = (a < b) and coalesce(not done, a <> b);
+    select a > b as bigger, done and a = 1, a in (1, 5), id between 2 and 3, not done, done is null from task order by id")?;
+    assert_eq!(column_values(&res[0], 0), values(&["1"]));
+    assert_eq!(column_values(&res[1], 0), values(&["2"]));
+    assert_eq!(column_values(&res[2], 0), values(&["2"]));
+    assert_eq!(column_values(&res[3], 0), values(&["2"]));
+    assert_eq!(res[4], QueryResult::Rows {
+        headers: vec![
+            String::from("bigger"),
+            String::from("done AND a = 1"),
+            String::from("a IN (1, 5)"),
+            String::from("id BETWEEN 2 AND 3"),
+            String::from("NOT done"),
+            String::from("done IS NULL"),
+        ],
+        rows: vec![
+            vec![Value::Boolean(false), Value::Boolean(true), Value::Boolean(true), Value::Boolean(false), Value::Boolean(false), Value::Boolean(false)],
+            vec![Value::Boolean(true), Value::Boolean(false), Value::Boolean(true), Value::Boolean(true), Value::Boolean(true), Value::Boolean(false)],
+            vec![Value::Boolean(false), Value::Boolean(false), Value::Boolean(false), Value::Boolean(true), Value::Null, Value::Boolean(true)],
+        ],
+    });
+    assert!(session.execute("select id from task where a + b").is_err());
+    assert!(session.execute("select id from task where a & b").is_err());
+    Ok(())
+}
+
+#[test]
+fn projection_test() -> Result<(), ExecutionError> {
+    let mut session = Session::from_directory("./fake_data/")?;
+    let res = session.execute("
+    select firstname || ' ' || lastname as full_name from user where id < 3;
+    select id, id * 10 as tens, 'x' from user where id < 3;
+    select u.* from user u join post p on u.id = p.author_id where p.id = 3;
+    select p.title, u.firstname from post p join user u on u.id = p.author_id where p.id = 1;
+    select id, -id as neg from user order by neg;
+    select firstname, length(firstname) from user order by 2 desc, 1;
+    select author_id, count(*) as n from post group by author_id order by n desc, author_id")?;
+    assert_eq!(res[0], QueryResult::Rows {
+        headers: vec![String::from("full_name")],
+        rows: vec![vec![Value::from("John Doe")], vec![Value::from("Jane Smith")]],
+    });
+    assert_eq!(res[1], QueryResult::Rows {
+        headers: vec![String::from("id"), String::from("tens"), String::from("'x'")],
+        rows: vec![
+            vec![Value::Integer(1), Value::Integer(10), Value::from("x")],
+            vec![Value::Integer(2), Value::Integer(20), Value::from("x")],
+        ],
+    });
+    assert_eq!(res[2], QueryResult::Rows {
+        headers: ["id", "firstname", "lastname", "email", "password"].iter().map(|h| h.to_string()).collect(),
+        rows: vec![values(&["2", "Jane", "Smith", "jane.smith@gmail.com", "qwerty"])],
+    });
+    assert_eq!(res[3], QueryResult::Rows {
+        headers: vec![String::from("title"), String::from("firstname")],
+        rows: vec![values(&["Hello world", "John"])],
+    });
+    assert_eq!(column_values(&res[4], 0), values(&["5", "4", "3", "2", "1"]));
+    assert_eq!(column_values(&res[5], 0), values(&["Mickael", "Alice", "Jane", "John", "Joe"]));
+    assert_eq!(column_values(&res[6], 0), values(&["1", "3", "2", "9"]));
+    assert!(session.execute("select x.* from user").is_err());
+    Ok(())
+}
+
+#[test]
+fn scalar_functions_test() -> Result<(), ExecutionError> {
+    let dir = ScratchDir::copy_of("./fake_data/", "scalar_functions");
+    let mut session = dir.session()?;
+    let res = session.execute("
+    select upper(lastname), substr(firstname, 1, 2), concat(id, '-', lower(firstname)) from user where id = 1;
+    select id from user where length(trim(lastname)) > 4 order by length(firstname) desc, id;
+    select cast(id as text) || 'x', cast('12' as integer) + 1, cast(2.6 as int), ceil(id / 2.0) from user where id = 3;
+    select trim(leading 'J' from firstname), substring(lastname from 2 for 3), round(avg(id) / 3.0, 2) from user where id = 4 group by firstname, lastname;
+    select coalesce(null, title), nullif(author_id, 9), ifnull(null, 0) from post where id = 6;
+    update user set firstname = upper(firstname), password = concat(password, id) where id < 3;
+    select firstname, password from user where id < 3")?;
+    assert_eq!(res[0], QueryResult::Rows {
+        headers: vec![String::from("upper(lastname)"), String::from("substr(firstname, 1, 2)"), String::from("concat(id, '-', lower(firstname))")],
+        rows: vec![values(&["DOE", "Jo", "1-john"])],
+    });
+    assert_eq!(column_values(&res[1], 0), values(&["3", "5", "2", "4"]));
+    assert_eq!(res[2], QueryResult::Rows {
+        headers: vec![
+            String::from("CAST(id AS TEXT) || 'x'"),
+            String::from("CAST('12' AS INTEGER) + 1"),
+            String::from("CAST(2.6 AS INT)"),
+            String::from("CEIL(id / 2.0)"),
+        ],
+        rows: vec![vec![Value::from("3x"), Value::Integer(13), Value::Integer(3), Value::Float(2.0)]],
+    });
+    assert_eq!(res[3], QueryResult::Rows {
+        headers: vec![
+            String::from("TRIM(LEADING 'J' FROM firstname)"),
+            String::from("SUBSTRING(lastname FROM 2 FOR 3)"),
+            String::from("round(avg(id) / 3.0, 2)"),
+        ],
+        rows: vec![vec![Value::from("oe"), Value::from("oga"), Value::Float(1.33)]],
+    });
+    assert_eq!(res[4], QueryResult::Rows {
+        headers: vec![String::from("coalesce(NULL, title)"), String::from("nullif(author_id, 9)"), String::from("ifnull(NULL, 0)")],
+        rows: vec![vec![Value::from("Lost post"), Value::Null, Value::Integer(0)]],
+    });
+    assert_eq!(res[5], QueryResult::AffectedRows(2));
+    assert_eq!(res[6], QueryResult::Rows {
+        headers: vec![String::from("firstname"), String::from("password")],
+        rows: vec![values(&["JOHN", "abcd12341"]), values(&["JANE", "qwerty2"])],
+    });
+    assert!(session.execute("select upper(firstname, lastname) from user").is_err());
+    assert!(session.execute("select cast(firstname as integer) from user").is_err());
+    Ok(())
+}
+
+#[test]
+fn date_functions_test() -> Result<(), ExecutionError> {
+    let dir = ScratchDir::copy_of("./fake_data/", "date_functions");
+    let mut session = dir.session()?;
+    let res = session.execute("
+    select id, extract(year from created), date_trunc('month', created) from post where id < 3;
+    select id from post where date '2023-05-31' < created + interval '1 month';
+    select created - interval '3' day, date_add(created, interval 1 year), strftime('%d/%m/%Y', created) from post where id = 4;
+    select created - date '2023-01-01' from post where id = 5;
+    select id from post where created < now() - interval '1 year' order by created desc;
+    update post set created = created + 7 where id = 6;
+    select created from post where id = 6")?;
+    assert_eq!(res[0], QueryResult::Rows {
+        headers: vec![String::from("id"), String::from("EXTRACT(YEAR FROM created)"), String::from("date_trunc('month', created)")],
+        rows: vec![values(&["1", "2022", "2022-11-01"]), values(&["2", "2023", "2023-02-01"])],
+    });
+    assert_eq!(column_values(&res[1], 0), values(&["4", "5"]));
+    assert_eq!(res[2], QueryResult::Rows {
+        headers: vec![
+            String::from("created - INTERVAL '3' DAY"),
+            String::from("date_add(created, INTERVAL 1 YEAR)"),
+            String::from("strftime('%d/%m/%Y', created)"),
+        ],
+        rows: vec![values(&["2023-04-28", "2024-05-01", "01/05/2023"])],
+    });
+    assert_eq!(column_values(&res[3], 0), values(&["162"]));
+    assert_eq!(column_values(&res[4], 0), values(&["5", "4", "2", "3", "1", "6"]));
+    assert_eq!(column_values(&res[6], 0), values(&["2021-01-08"]));
+    assert!(session.execute("select interval 'soon' from post").is_err());
+    Ok(())
+}
+
+#[test]
+fn case_when_test() -> Result<(), ExecutionError> {
+    let dir = ScratchDir::copy_of("./fake_data/", "case_when");
+    let mut session = dir.session()?;
+    let res = session.execute("
+    select id, case when author_id = 1 then 'first' when author_id < 3 then 'early' else 'late' end as bucket from post order by id;
+    select case author_id when 1 then 'one' when 1 + 1 then 'two' end from post where id in (1, 3, 4);
+    select id from post where case when author_id = 9 then 0 else 1 end = 0;
+    select id from user order by case when firstname like 'J%' then 0 else 1 end, id;
+    select author_id, case when count(*) > 1 then 'many' else 'one' end from post group by author_id;
+    update post set title = case when author_id = 9 then 'Orphan' else title end;
+    select title from post where id in (1, 6)")?;
+    assert_eq!(res[0], QueryResult::Rows {
+        headers: vec![String::from("id"), String::from("bucket")],
+        rows: vec![
+            values(&["1", "first"]),
+            values(&["2", "first"]),
+            values(&["3", "early"]),
+            values(&["4", "late"]),
+            values(&["5", "late"]),
+            values(&["6", "late"]),
+        ],
+    });
+    assert_eq!(column_values(&res[1], 0), vec![Value::from("one"), Value::from("two"), Value::Null]);
+    assert_eq!(column_values(&res[2], 0), values(&["6"]));
+    assert_eq!(column_values(&res[3], 0), values(&["1", "2", "4", "3", "5"]));
+    assert_eq!(column_values(&res[4], 1), values(&["many", "one", "many", "one"]));
+    assert_eq!(res[5], QueryResult::AffectedRows(6));
+    assert_eq!(column_values(&res[6], 0), values(&["Hello world", "Orphan"]));
+    Ok(())
+}
+
+#[test]
+fn distinct_test() -> Result<(), ExecutionError> {
+    let mut session = Session::from_directory("./fake_data/")?;
+    let res = session.execute("
+    select distinct follower_id from following;
+    select distinct author_id from post order by author_id desc;
+    select distinct author_id % 2 as parity from post;
+    select distinct on (author_id) author_id, title from post order by author_id, created desc;
+    select distinct on (a) author_id as a, id from post order by a, id desc;
+    select distinct p.author_id, u.firstname from post p join user u on u.id = p.author_id")?;
+    assert_eq!(column_values(&res[0], 0), values(&["1", "2", "3", "4"]));
+    assert_eq!(column_values(&res[1], 0), values(&["9", "3", "2", "1"]));
+    assert_eq!(res[2], QueryResult::Rows {
+        headers: vec![String::from("parity")],
+        rows: vec![values(&["1"]), values(&["0"])],
+    });
+    assert_eq!(column_values(&res[3], 1), values(&["Back again", "Cooking tips", "Training", "Lost post"]));
+    assert_eq!(column_values(&res[4], 1), values(&["2", "3", "5", "6"]));
+    assert_eq!(column_values(&res[5], 1), values(&["John", "Jane", "Mickael"]));
+    Ok(())
+}
+
+#[test]
+fn subqueries_test() -> Result<(), ExecutionError> {
+    let dir = ScratchDir::copy_of("./fake_data/", "subqueries");
+    let mut session = dir.session()?;
+    let res = session.execute("
+    select firstname from user u where id in (select author_id from post);
+    select firstname from user u where not exists (select 1 from post p where p.author_id = u.id);
+    select title from post where created = (select max(created) from post);
+    select firstname, (select count(*) from post p where p.author_id = u.id) as posts from user u order by posts desc, id;
+    select title, (select firstname from user u where u.id = author_id) from post order by id;
+    select firstname from user u where exists (
+        select 1 from following f where f.follower_id = u.id and exists (
+            select 1 from post p where p.author_id = f.followed_id and p.author_id <> u.id
+        )
+    )")?;
+    assert_eq!(column_values(&res[0], 0), values(&["John", "Jane", "Mickael"]));
+    assert_eq!(column_values(&res[1], 0), values(&["Joe", "Alice"]));
+    assert_eq!(column_values(&res[2], 0), values(&["Training"]));
+    assert_eq!(column_values(&res[3], 0), values(&["John", "Mickael", "Jane", "Joe", "Alice"]));
+    assert_eq!(column_values(&res[3], 1), values(&["2", "2", "1", "0", "0"]));
+    assert_eq!(column_values(&res[4], 1)[..5], values(&["John", "John", "Jane", "Mickael", "Mickael"]));
+    assert_eq!(column_values(&res[4], 1)[5], Value::Null);
+    assert_eq!(column_values(&res[5], 0), values(&["John", "Jane"]));
+    assert!(session.execute("select title, (select firstname from user u) from post").is_err());
+    assert!(session.execute("select title from post where author_id in (select id, firstname from user u)").is_err());
+    // Subqueries which fail for another reason than referring to the statement records report it, even without records
+    for (sql, reason) in [
+        ("with none as (select id from post where id > 10) select id from none where id in (select id / 0 from user u)", "Division by zero"),
+        ("with none as (select id from post where id > 10) select id from none where id in (select id from users)", "Unknown table users"),
+        ("with none as (select id from post where id > 10) select id from none where id in (select nickname from user u)", "Unknown column nickname"),
+    ] {
+        match session.execute(sql) {
+            Err(ExecutionError::Querying(e)) => assert_eq!(e.reason(), reason, "{}", sql),
+            other => panic!("{} gave {:?}", sql, other),
+        }
+    }
+    let res = session.execute("
+    delete from post where author_id not in (select id from user);
+    select id from post;
+    update post set content = (select firstname from user u where u.id = post.author_id)
+    where author_id in (select followed_id from following f where f.follower_id = 1);
+    select content from post order by id")?;
+    assert_eq!(res[0], QueryResult::AffectedRows(1));
+    assert_eq!(column_values(&res[1], 0), values(&["1", "2", "3", "4", "5"]));
+    assert_eq!(res[2], QueryResult::AffectedRows(3));
+    assert_eq!(column_values(&res[3], 0), values(&["My very first post", "Still here", "Jane", "Mickael", "Mickael"]));
+    Ok(())
+}
+
+#[test]
+fn correlated_subqueries_test() -> Result<(), ExecutionError> {
+    let mut session = Session::from_directory("./fake_data/")?;
+    let res = session.execute("
+    select id from post p where exists (
+        select 1 from user u where u.id = p.author_id and exists (
+            select 1 from following f where f.follower_id = u.id and f.followed_id = p.id
+        )
+    ) order by id;
+    select title from post p where author_id = (select p.author_id from post p where p.id = 3);
+    select id, (select count(*) from post q where q.author_id = p.author_id) from post p order by id")?;
+    // The statement records are read by the innermost subquery only
+    assert_eq!(column_values(&res[0], 0), values(&["2", "3", "4"]));
+    // Columns of a table of the subquery hide the ones of the statement named the same way
+    assert_eq!(column_values(&res[1], 0), values(&["Cooking tips"]));
+    // Records sharing the values read by the subquery share its rows
+    assert_eq!(column_values(&res[2], 1), values(&["2", "2", "1", "2", "2", "1"]));
+    let mut t = Table::new("amount", &vec!["id", "value"]).unwrap();
+    for (id, value) in [(1, Value::Integer(1)), (2, Value::Float(1.0)), (3, Value::Integer(1))] {
+        t.insert(&None, InsertElement::PlainValues(vec![Value::Integer(id), value]))?;
+    }
+    let mut session = Session::new(DataStore::new(vec![t]));
+    let res = session.execute("select (select a.value) from amount a order by id")?;
+    // Equal numbers of different types do not share the rows of the subquery
+    let texts: Vec<String> = column_values(&res[0], 0).iter().map(|v| v.to_string()).collect();
+    assert_eq!(texts, vec!["1", "1.0", "1"]);
+    Ok(())
+}
+
+#[test]
+fn set_operations_test() -> Result<(), ExecutionError> {
+    let mut session = Session::from_directory("./samples/exports/")?;
+    let res = session.execute("
+    select customer from sales_january union select customer from sales_february order by customer;
+    select product, amount from sales_january union all select product, amount from sales_february;
+    select customer from sales_january intersect select customer from sales_february;
+    select customer from sales_january except select customer from sales_february;
+    select product from sales_january intersect all select product from sales_february order by 1;
+    select product from sales_january except all select product from sales_february;
+    select customer as buyer, day from sales_january where amount > 100
+    union select customer, day from sales_february where amount > 100
+    order by day desc limit 2 offset 1;
+    (select customer from sales_january except select customer from sales_february) union select customer from sales_february where product = 'Desk'")?;
+    assert_eq!(column_values(&res[0], 0), values(&["Acme", "Globex", "Initech", "Umbrella"]));
+    assert_eq!(column_values(&res[1], 0).len(), 7);
+    assert_eq!(column_values(&res[2], 0), values(&["Acme", "Globex"]));
+    assert_eq!(column_values(&res[3], 0), values(&["Initech"]));
+    assert_eq!(column_values(&res[4], 0), values(&["Chair", "Desk", "Lamp"]));
+    assert_eq!(column_values(&res[5], 0), values(&["Chair"]));
+    match &res[6] {
+        QueryResult::Rows { headers, rows } => {
+            assert_eq!(headers, &vec![String::from("buyer"), String::from("day")]);
+            assert_eq!(rows, &vec![values(&["Globex", "2023-02-02"]), values(&["Initech", "2023-01-28"])]);
+        },
+        QueryResult::AffectedRows(_) => panic!("UNION should return rows"),
+    }
+    assert_eq!(column_values(&res[7], 0), values(&["Initech", "Umbrella"]));
+    for sql in [
+        "select customer, product from sales_january union select customer from sales_february",
+        "select amount from sales_january union select day from sales_february",
+        "select amount from sales_january union select customer from sales_february",
+        "select customer from sales_january except select day from sales_february",
+    ] {
+        assert!(session.execute(sql).is_err(), "{}", sql);
+    }
+    Ok(())
+}
+
+#[test]
+fn common_tables_test() -> Result<(), ExecutionError> {
+    let mut session = Session::from_directory("./fake_data/")?;
+    let res = session.execute("
+    with prolific as (select author_id, count(*) as posts from post group by author_id)
+    select firstname, posts from user u join prolific p on p.author_id = u.id order by posts desc, firstname;
+    with t(a, n) as (select id, firstname from user) select n from t where a > 3;
+    with low as (select id from user where id < 4), middle as (select id from low where id > 1) select id from middle;
+    with post as (select 1 as id) select count(*) from post;
+    with recursive reachable(id) as (
+        select followed_id from following where follower_id = 4
+        union
+        select f.followed_id from following f join reachable r on f.follower_id = r.id
+    ) select id from reachable order by id;
+    with recursive reachable(id) as (
+        select followed_id from following where follower_id = 1
+        union
+        select f.followed_id from following f join reachable r on f.follower_id = r.id
+    ) select id from reachable order by id;
+    with recursive network(id, depth) as (
+        select followed_id, 1 from following where follower_id = 1
+        union all
+        select f.followed_id, n.depth + 1 from following f join network n on f.follower_id = n.id where n.depth < 2
+    )
+    select distinct u.firstname from network n join user u on u.id = n.id
+    where n.depth = 2 and n.id <> 1 and n.id not in (select followed_id from following where follower_id = 1);
+    with recursive t(n) as (select 1 union all select n + 1 from t where n < 5) select sum(n) from t")?;
+    assert_eq!(column_values(&res[0], 0), values(&["John", "Mickael", "Jane"]));
+    assert_eq!(column_values(&res[0], 1), values(&["2", "2", "1"]));
+    assert_eq!(column_values(&res[1], 0), values(&["Joe", "Alice"]));
+    assert_eq!(column_values(&res[2], 0), values(&["2", "3"]));
+    assert_eq!(column_values(&res[3], 0), values(&["1"]));
+    assert_eq!(column_values(&res[4], 0), values(&["5"]));
+    assert_eq!(column_values(&res[5], 0), values(&["1", "2", "3", "4", "5"]));
+    assert_eq!(column_values(&res[6], 0), values(&["Joe"]));
+    assert_eq!(column_values(&res[7], 0), values(&["15"]));
+    for sql in [
+        "with t(a, b) as (select id from user) select a from t",
+        "with recursive walk(id) as (
+            select followed_id from following where follower_id = 1 and followed_id = 2
+            union all
+            select f.followed_id from following f join walk w on f.follower_id = w.id
+        ) select id from walk",
+    ] {
+        assert!(session.execute(sql).is_err(), "{}", sql);
+    }
+    Ok(())
+}
+
+#[test]
+fn window_functions_test() -> Result<(), ExecutionError> {
+    let mut session = Session::from_directory("./fake_data/")?;
+    let res = session.execute("
+    select id, row_number() over (partition by author_id order by created desc) as latest from post order by id;
+    select id, rank() over (order by author_id), dense_rank() over (order by author_id) from post order by id;
+    select id, lag(id) over (order by id), lead(id, 2, 0) over (order by id) from post order by id;
+    select id, first_value(title) over (partition by author_id order by id) from post order by id;
+    select id, sum(id) over (order by id), avg(id) over (order by id rows between 1 preceding and current row) from post;
+    select id, sum(author_id) over (order by author_id), count(*) over (partition by author_id) from post order by id;
+    select author_id, rank() over (order by count(*) desc) from post group by author_id order by author_id;
+    select id from post order by row_number() over (order by created) desc limit 2")?;
+    assert_eq!(column_values(&res[0], 1), values(&["2", "1", "1", "2", "1", "1"]));
+    assert_eq!(column_values(&res[1], 1), values(&["1", "1", "3", "4", "4", "6"]));
+    assert_eq!(column_values(&res[1], 2), values(&["1", "1", "2", "3", "3", "4"]));
+    assert_eq!(column_values(&res[2], 1)[0], Value::Null);
+    assert_eq!(column_values(&res[2], 1)[1..], values(&["1", "2", "3", "4", "5"]));
+    assert_eq!(column_values(&res[2], 2), values(&["3", "4", "5", "6", "0", "0"]));
+    assert_eq!(column_values(&res[3], 1)[..5], values(&["Hello world", "Hello world", "Cooking tips", "Basketball", "Basketball"]));
+    assert_eq!(column_values(&res[4], 1), values(&["1", "3", "6", "10", "15", "21"]));
+    assert_eq!(column_values(&res[4], 2), vec![1.0, 1.5, 2.5, 3.5, 4.5, 5.5].into_iter().map(Value::Float).collect::<Vec<Value>>());
+    // Records sorted as equal are part of the default frame
+    assert_eq!(column_values(&res[5], 1), values(&["2", "2", "4", "10", "10", "19"]));
+    assert_eq!(column_values(&res[5], 2), values(&["2", "2", "1", "2", "2", "1"]));
+    assert_eq!(column_values(&res[6], 1), values(&["1", "3", "1", "3"]));
+    assert_eq!(column_values(&res[7], 0), values(&["5", "4"]));
+
+    let mut ledger = Session::from_directory("./samples/exports/")?;
+    let res = ledger.execute("
+    select customer, sum(amount) over (partition by customer order by day) from sales_january order by customer, day")?;
+    assert_eq!(column_values(&res[0], 1), values(&["120.5", "165.5", "300", "120.5"]));
+    for sql in [
+        "select id, sum(id) over (order by id range between 1 preceding and current row) from post",
+        "select id, ntile(2) over (order by id) from post",
+        "select id, lag(id, -1) over (order by id) from post",
+    ] {
+        assert!(session.execute(sql).is_err(), "{}", sql);
+    }
+    Ok(())
+}
+
+#[test]
+fn multi_row_insert_test() -> Result<(), ExecutionError> {
+    let dir = ScratchDir::copy_of("./fake_data/", "multi_row_insert");
+    let mut session = dir.session()?;
+    let res = session.execute("
+    insert into post (id, author_id, title) values (7, 1, 'First'), (8, 2, 'Second'), (9, 3, 'Third');
+    insert into post values (10, 1, 'Fourth', NULL, '2023-08-01'), (11, 2, 'Fifth', 'Last one', '2023-08-02');
+    select title from post where id > 6 order by id")?;
+    assert_eq!(res[0], QueryResult::AffectedRows(3));
+    assert_eq!(res[1], QueryResult::AffectedRows(2));
+    assert_eq!(column_values(&res[2], 0), values(&["First", "Second", "Third", "Fourth", "Fifth"]));
+    // A single failing row leaves the table as it was
+    for sql in [
+        "insert into post (id, author_id) values (12, 1), (12, 2)",
+        "insert into post (id, author_id) values (12, 1), (1, 2)",
+        "insert into post (id, author_id) values (12, 1), (13, NULL)",
+        "insert into post (id, author_id) values (12, 1), (13)",
+    ] {
+        assert!(session.execute(sql).is_err(), "{}", sql);
+    }
+    let res = session.execute("select count(*) from post")?;
+    assert_eq!(column_values(&res[0], 0), values(&["11"]));
+    // Values are computed as in a SELECT
+    let res = session.execute("
+    insert into post (id, author_id, title, created) values (-5, 1 + 1, upper('negative'), DATE '2023-01-01' + 1), (12, (select max(id) from user), 'Latest', NULL);
+    select id, author_id, title, created from post where id < 0 or id = 12 order by id")?;
+    assert_eq!(res[0], QueryResult::AffectedRows(2));
+    assert_eq!(res[1], QueryResult::Rows {
+        headers: vec![String::from("id"), String::from("author_id"), String::from("title"), String::from("created")],
+        rows: vec![
+            vec![Value::Integer(-5), Value::Integer(2), Value::infer("NEGATIVE"), Value::infer("2023-01-02")],
+            vec![Value::Integer(12), Value::Integer(5), Value::infer("Latest"), Value::Null],
+        ],
+    });
+    let err = session.execute("insert into post (id, author_id) values (13, 1, 'Too many')").unwrap_err();
+    assert!(err.to_string().ends_with("2 columns listed, but the row of VALUES has 3 columns"), "{}", err);
+    assert!(session.execute("insert into post (id, author_id) values (13, 1 / 0)").is_err());
+    Ok(())
+}
+
+#[test]
+fn insert_select_test() -> Result<(), ExecutionError> {
+    let dir = ScratchDir::copy_of("./samples/exports/", "insert_select_sales");
+    let mut session = dir.session()?;
+    let res = session.execute("
+    insert into sales_january select * from sales_february where amount > 100;
+    insert into sales_january (id, customer, amount) select id + 10, customer, amount from sales_february order by id limit 2;
+    select id, product from sales_january where id > 4 or day > '2023-01-31' order by id")?;
+    assert_eq!(res[0], QueryResult::AffectedRows(2));
+    assert_eq!(res[1], QueryResult::AffectedRows(2));
+    assert_eq!(column_values(&res[2], 0), values(&["1", "3", "11", "12"]));
+    assert_eq!(column_values(&res[2], 1)[..2], values(&["Chair", "Desk"]));
+    assert_eq!(column_values(&res[2], 1)[2], Value::Null);
+
+    let dir = ScratchDir::copy_of("./fake_data/", "insert_select_posts");
+    let mut session = dir.session()?;
+    let res = session.execute("
+    insert into post (id, author_id, title) select id + 10, author_id, title from post where author_id = 1;
+    select title from post where id > 10")?;
+    assert_eq!(res[0], QueryResult::AffectedRows(2));
+    assert_eq!(column_values(&res[1], 0), values(&["Hello world", "Back again"]));
+    for sql in [
+        "insert into post (id, author_id) select id + 20, author_id from post union all select 1, 1",
+        "insert into post (id, author_id) select id from post",
+        "insert into post select id + 20, author_id from post",
+        "insert into post select * from unknown",
+    ] {
+        assert!(session.execute(sql).is_err(), "{}", sql);
+    }
+    let res = session.execute("select count(*) from post")?;
+    assert_eq!(column_values(&res[0], 0), values(&["8"]));
+    Ok(())
+}
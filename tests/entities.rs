@@ -1,6 +1,6 @@
 use std::collections::HashMap;
 
-use csql::{entities::*, errors::TableInitError, traits::{Queryable, Columns, InsertElement, Condition, Recordable}};
+use csql::{entities::*, errors::TableInitError, traits::{Queryable, Columns, InsertElement, Condition, ComparisonOperator, Recordable}, utils::Value};
 
 #[test]
 fn table_creation_test() {
@@ -17,40 +17,40 @@ fn insert_test() -> Result<(), TableInitError> {
     let mut t = Table::new("", &vec!["id", "username", "password"])?;
     let lines = vec![
         vec![
-            Some(String::from("1")),
-            Some(String::from("john.doe123")),
-            Some(String::from("abcd1234")),
+            Value::Integer(1),
+            Value::Text(String::from("john.doe123")),
+            Value::Text(String::from("abcd1234")),
         ],
         vec![
-            Some(String::from("2")),
-            Some(String::from("jrogan_$89")),
-            Some(String::from("zzz")),
+            Value::Integer(2),
+            Value::Text(String::from("jrogan_$89")),
+            Value::Text(String::from("zzz")),
         ],
         vec![
-            Some(String::from("3")),
-            Some(String::from("mickael76")),
-            Some(String::from("okokokok")),
+            Value::Integer(3),
+            Value::Text(String::from("mickael76")),
+            Value::Text(String::from("okokokok")),
         ],
     ];
     for l in lines {
         let r = t.insert(&None, InsertElement::PlainValues(l));
-        if let Err(_) = r {
+        if r.is_err() {
             return r.map_err(|_| TableInitError::new("Error while inserting element..."));
         }
     };
     let res = t.insert(&None, InsertElement::PlainValues(vec![
-        Some(String::from("3")),
-        Some(String::from("mickael76")),
-        Some(String::from("okokokok")),
+        Value::Integer(3),
+        Value::Text(String::from("mickael76")),
+        Value::Text(String::from("okokokok")),
     ]));
     assert!(res.is_ok());
     let mut iter = t.iter().map(|r| r.get_record_as_collection());
     iter.next();
     iter.next();
     let expected = vec![
-        Some(String::from("3")),
-        Some(String::from("mickael76")),
-        Some(String::from("okokokok")),
+        Value::Integer(3),
+        Value::Text(String::from("mickael76")),
+        Value::Text(String::from("okokokok")),
     ];
     assert_eq!(iter.next(), Some(expected));
     Ok(())
@@ -61,24 +61,24 @@ fn select_one_column_test() -> Result<(), TableInitError> {
     let mut t = Table::new("", &vec!["id", "username", "password"])?;
     let lines = vec![
         vec![
-            Some(String::from("1")),
-            Some(String::from("john.doe123")),
-            Some(String::from("abcd1234")),
+            Value::Integer(1),
+            Value::Text(String::from("john.doe123")),
+            Value::Text(String::from("abcd1234")),
         ],
         vec![
-            Some(String::from("2")),
-            Some(String::from("jrogan_$89")),
-            Some(String::from("zzz")),
+            Value::Integer(2),
+            Value::Text(String::from("jrogan_$89")),
+            Value::Text(String::from("zzz")),
         ],
     ];
     for l in lines {
         let r = t.insert(&None, InsertElement::PlainValues(l));
-        if let Err(_) = r {
+        if r.is_err() {
             return r.map_err(|_| TableInitError::new("Error while inserting element..."));
         }
     }
     let query_res = t.select(&None, &Columns::ColumnNames(vec![String::from("id")]),& None);
-    assert_eq!(query_res, Ok(vec![vec![Some(String::from("1"))], vec![Some(String::from("2"))]]));
+    assert_eq!(query_res, Ok(vec![vec![Value::Integer(1)], vec![Value::Integer(2)]]));
     Ok(())
 }
 
@@ -87,26 +87,26 @@ fn select_multiple_columns_test() -> Result<(), TableInitError> {
     let mut t = Table::new("", &vec!["id", "username", "password"])?;
     let lines = vec![
         vec![
-            Some(String::from("1")),
-            Some(String::from("john.doe123")),
-            Some(String::from("abcd1234")),
+            Value::Integer(1),
+            Value::Text(String::from("john.doe123")),
+            Value::Text(String::from("abcd1234")),
         ],
         vec![
-            Some(String::from("2")),
-            Some(String::from("jrogan_$89")),
-            Some(String::from("zzz")),
+            Value::Integer(2),
+            Value::Text(String::from("jrogan_$89")),
+            Value::Text(String::from("zzz")),
         ],
     ];
     for l in lines {
         let r = t.insert(&None, InsertElement::PlainValues(l));
-        if let Err(_) = r {
+        if r.is_err() {
             return r.map_err(|_| TableInitError::new("Error while inserting element..."));
         }
     }
     let query_res = t.select(&None, &Columns::ColumnNames(vec![String::from("id"), String::from("password")]), &None);
     assert_eq!(query_res, Ok(vec![
-        vec![Some(String::from("1")), Some(String::from("abcd1234"))],
-        vec![Some(String::from("2")), Some(String::from("zzz"))],
+        vec![Value::Integer(1), Value::Text(String::from("abcd1234"))],
+        vec![Value::Integer(2), Value::Text(String::from("zzz"))],
     ]));
     Ok(())
 }
@@ -116,33 +116,33 @@ fn select_filtered_columns_test() -> Result<(), TableInitError> {
     let mut t = Table::new("", &vec!["id", "username", "password"])?;
     let lines = vec![
         vec![
-            Some(String::from("1")),
-            Some(String::from("john.doe123")),
-            Some(String::from("abcd1234")),
+            Value::Integer(1),
+            Value::Text(String::from("john.doe123")),
+            Value::Text(String::from("abcd1234")),
         ],
         vec![
-            Some(String::from("2")),
-            Some(String::from("jrogan_$89")),
-            Some(String::from("zzz")),
+            Value::Integer(2),
+            Value::Text(String::from("jrogan_$89")),
+            Value::Text(String::from("zzz")),
         ],
     ];
     for l in lines {
         let r = t.insert(&None, InsertElement::PlainValues(l));
-        if let Err(_) = r {
+        if r.is_err() {
             return r.map_err(|_| TableInitError::new("Error while inserting element..."));
         }
     }
 
-    let conditions_1 = Condition::Equal(String::from("id"), String::from("1"));
+    let conditions_1 = Condition::column_compared("id", ComparisonOperator::Equal, Value::Integer(1));
     let conditions_2 = Condition::And(
-        Box::new(Condition::Equal(String::from("id"), String::from("1"))),
-        Box::new(Condition::Equal(String::from("password"), String::from("password_non_existent"))),
+        Box::new(Condition::column_compared("id", ComparisonOperator::Equal, Value::Integer(1))),
+        Box::new(Condition::column_compared("password", ComparisonOperator::Equal, Value::Text(String::from("password_non_existent")))),
     );
     let query_res_1 = t.select(&None, &Columns::All, &Some(conditions_1));
     let query_res_2 = t.select(&None, &Columns::All, &Some(conditions_2));
     
     assert_eq!(query_res_1, Ok(vec![
-        vec![Some(String::from("1")), Some(String::from("john.doe123")), Some(String::from("abcd1234"))],
+        vec![Value::Integer(1), Value::Text(String::from("john.doe123")), Value::Text(String::from("abcd1234"))],
     ]));
     assert_eq!(query_res_2, Ok(vec![]));
 
@@ -154,19 +154,19 @@ fn delete_test() -> Result<(), TableInitError> {
     let mut t = Table::new("", &vec!["id", "username", "password"])?;
     let lines = vec![
         vec![
-            Some(String::from("1")),
-            Some(String::from("john.doe123")),
-            Some(String::from("abcd1234")),
+            Value::Integer(1),
+            Value::Text(String::from("john.doe123")),
+            Value::Text(String::from("abcd1234")),
         ],
         vec![
-            Some(String::from("2")),
-            Some(String::from("jrogan_$89")),
-            Some(String::from("zzz")),
+            Value::Integer(2),
+            Value::Text(String::from("jrogan_$89")),
+            Value::Text(String::from("zzz")),
         ],
     ];
     for l in lines {
         let r = t.insert(&None, InsertElement::PlainValues(l));
-        if let Err(_) = r {
+        if r.is_err() {
             return r.map_err(|_| TableInitError::new("Error while inserting element..."));
         }
     }
@@ -181,35 +181,35 @@ fn delete_filtered_test() -> Result<(), TableInitError> {
     let mut t = Table::new("", &vec!["id", "username", "password"])?;
     let lines = vec![
         vec![
-            Some(String::from("1")),
-            Some(String::from("john.doe123")),
-            Some(String::from("abcd1234")),
+            Value::Integer(1),
+            Value::Text(String::from("john.doe123")),
+            Value::Text(String::from("abcd1234")),
         ],
         vec![
-            Some(String::from("2")),
-            Some(String::from("jrogan_$89")),
-            Some(String::from("zzz")),
+            Value::Integer(2),
+            Value::Text(String::from("jrogan_$89")),
+            Value::Text(String::from("zzz")),
         ],
         vec![
-            Some(String::from("3")),
-            Some(String::from("mickael76")),
-            Some(String::from("okokokok")),
+            Value::Integer(3),
+            Value::Text(String::from("mickael76")),
+            Value::Text(String::from("okokokok")),
         ],
     ];
     for l in lines {
         let r = t.insert(&None, InsertElement::PlainValues(l));
-        if let Err(_) = r {
+        if r.is_err() {
             return r.map_err(|_| TableInitError::new("Error while inserting element..."));
         }
     };
-    let conditions = Condition::LessThan(String::from("id"), String::from("3"));
+    let conditions = Condition::column_compared("id", ComparisonOperator::LessThan, Value::Integer(3));
     let query_res = t.delete(&None, &Some(conditions));
     let mut iter = t.iter().map(|r| r.get_record_as_collection());
     assert!(query_res.is_ok());
     let expected = vec![
-        Some(String::from("3")),
-        Some(String::from("mickael76")),
-        Some(String::from("okokokok")),
+        Value::Integer(3),
+        Value::Text(String::from("mickael76")),
+        Value::Text(String::from("okokokok")),
     ];
     
     assert_eq!(iter.next(), Some(expected));
@@ -222,47 +222,47 @@ fn update_test() -> Result<(), TableInitError> {
     let mut t = Table::new("", &vec!["id", "username", "password"])?;
     let lines = vec![
         vec![
-            Some(String::from("1")),
-            Some(String::from("john.doe123")),
-            Some(String::from("abcd1234")),
+            Value::Integer(1),
+            Value::Text(String::from("john.doe123")),
+            Value::Text(String::from("abcd1234")),
         ],
         vec![
-            Some(String::from("2")),
-            Some(String::from("jrogan_$89")),
-            Some(String::from("zzz")),
+            Value::Integer(2),
+            Value::Text(String::from("jrogan_$89")),
+            Value::Text(String::from("zzz")),
         ],
         vec![
-            Some(String::from("3")),
-            Some(String::from("mickael76")),
-            Some(String::from("okokokok")),
+            Value::Integer(3),
+            Value::Text(String::from("mickael76")),
+            Value::Text(String::from("okokokok")),
         ],
     ];
     for l in lines {
         let r = t.insert(&None, InsertElement::PlainValues(l));
-        if let Err(_) = r {
+        if r.is_err() {
             return r.map_err(|_| TableInitError::new("Error while inserting element..."));
         }
     };
-    let updated_elements: HashMap<String, Option<String>> = HashMap::from([(
+    let updated_elements: HashMap<String, Value> = HashMap::from([(
         String::from("username"),
-        Some(String::from("New name here !"))
+        Value::Text(String::from("New name here !"))
     )]);
     let res = t.update(&None, updated_elements, &None);
     assert!(res.is_ok());
     let e1 = vec![
-        Some(String::from("1")),
-        Some(String::from("New name here !")),
-        Some(String::from("abcd1234")),
+        Value::Integer(1),
+        Value::Text(String::from("New name here !")),
+        Value::Text(String::from("abcd1234")),
     ];
     let e2 = vec![
-        Some(String::from("2")),
-        Some(String::from("New name here !")),
-        Some(String::from("zzz")),
+        Value::Integer(2),
+        Value::Text(String::from("New name here !")),
+        Value::Text(String::from("zzz")),
     ];
     let e3 = vec![
-        Some(String::from("3")),
-        Some(String::from("New name here !")),
-        Some(String::from("okokokok")),
+        Value::Integer(3),
+        Value::Text(String::from("New name here !")),
+        Value::Text(String::from("okokokok")),
     ];
     let mut iter = t.iter().map(|r| r.get_record_as_collection());
     assert_eq!(iter.next(), Some(e1));
@@ -277,50 +277,50 @@ fn update_filtered_test() -> Result<(), TableInitError>{
     let mut t = Table::new("", &vec!["id", "username", "password"])?;
     let lines = vec![
         vec![
-            Some(String::from("1")),
-            Some(String::from("john.doe123")),
-            Some(String::from("abcd1234")),
+            Value::Integer(1),
+            Value::Text(String::from("john.doe123")),
+            Value::Text(String::from("abcd1234")),
         ],
         vec![
-            Some(String::from("2")),
-            Some(String::from("jrogan_$89")),
-            Some(String::from("zzz")),
+            Value::Integer(2),
+            Value::Text(String::from("jrogan_$89")),
+            Value::Text(String::from("zzz")),
         ],
         vec![
-            Some(String::from("3")),
-            Some(String::from("mickael76")),
-            Some(String::from("okokokok")),
+            Value::Integer(3),
+            Value::Text(String::from("mickael76")),
+            Value::Text(String::from("okokokok")),
         ],
     ];
     for l in lines {
         let r = t.insert(&None, InsertElement::PlainValues(l));
-        if let Err(_) = r {
+        if r.is_err() {
             return r.map_err(|_| TableInitError::new("Error while inserting element..."));
         }
     };
 
-    let updated_elements: HashMap<String, Option<String>> = HashMap::from([(
+    let updated_elements: HashMap<String, Value> = HashMap::from([(
         String::from("username"),
-        Some(String::from("New name here !"))
+        Value::Text(String::from("New name here !"))
     )]);
-    let conditions = Condition::Equal(String::from("id"), String::from("1"));
+    let conditions = Condition::column_compared("id", ComparisonOperator::Equal, Value::Integer(1));
     let res = t.update(&None, updated_elements, &Some(conditions));
     
     assert!(res.is_ok());
     let e1 = vec![
-        Some(String::from("1")),
-        Some(String::from("New name here !")),
-        Some(String::from("abcd1234")),
+        Value::Integer(1),
+        Value::Text(String::from("New name here !")),
+        Value::Text(String::from("abcd1234")),
     ];
     let e2 = vec![
-        Some(String::from("2")),
-        Some(String::from("jrogan_$89")),
-        Some(String::from("zzz")),
+        Value::Integer(2),
+        Value::Text(String::from("jrogan_$89")),
+        Value::Text(String::from("zzz")),
     ];
     let e3 = vec![
-        Some(String::from("3")),
-        Some(String::from("mickael76")),
-        Some(String::from("okokokok")),
+        Value::Integer(3),
+        Value::Text(String::from("mickael76")),
+        Value::Text(String::from("okokokok")),
     ];
     let mut iter = t.iter().map(|r| r.get_record_as_collection());
     assert_eq!(iter.next(), Some(e1));
@@ -1,48 +1,61 @@
-use csql::entities::{Table, Directory};
-
-use csql::errors::{TableInitError, LoadingError};
-
-use csql::file_parsing::{Buffer, Source};
-
-use csql::traits::{Queryable, Loadable, Columns, Condition, SourceType, Data};
-
-// #[test]
-// fn apply_select() -> Result<(), TableInitError> {
-//     let buf = Buffer::new(Source::FilePath(String::from("samples/example1.csv")));
-//     let mut t = Table::new(None, &vec!["one", "two", "three"])?;
-//     t.bulk_load_data(&buf.bulk_data(3).unwrap()).unwrap();
-//     let expected: Vec<Vec<Option<String>>> = vec![
-//         vec![Some(String::from("a")), Some(String::from("c"))],
-//         vec![Some(String::from("1")), Some(String::from("3"))],
-//         vec![Some(String::from("x")), Some(String::from("z"))],
-//     ];
-//     let query_res = t.select(
-//         Columns::ColumnNames(vec![String::from("one"), String::from("three")]),
-//         &None
-//     ).unwrap();
-//     assert_eq!(query_res, expected);
-//     Ok(())
-// }
-
-// #[test]
-// fn dump_data_test() -> Result<(), TableInitError> {
-//     let buf = Buffer::load_from_source("samples/example1.csv", SourceType::LocalFile).unwrap();
-//     let mut t = Table::new(None, &vec!["one", "two", "three"])?;
-//     t.bulk_load_data(&buf.bulk_data(3).unwrap()).unwrap();
-//     t.delete(&Some(Condition::Equal(String::from("one"), String::from("a")))).unwrap();
-//     let new_data = t.get_records_as_collection();
-//     let expected: Vec<Vec<Option<String>>> = vec![
-//         vec![Some(String::from("1")), Some(String::from("2")), Some(String::from("3"))],
-//         vec![Some(String::from("x")), Some(String::from("y")), Some(String::from("z"))],
-//     ];
-//     assert_eq!(expected, new_data);
-//     println!("New data : {:?}", new_data);
-//     let res = buf.dump_data(new_data);
-//     assert!(res.is_ok());
-//     let updated_data = buf.bulk_data(3).unwrap();
-//     assert_eq!(expected, updated_data);
-//     Ok(())
-// }
+use std::fs;
+
+use csql::entities::{Directory, Table};
+use csql::file_parsing::{Buffer, Dialect, Source};
+use csql::schema::ColumnType;
+use csql::traits::{Columns, ComparisonOperator, Condition, Data, InsertElement, Loadable, Queryable, SourceType, Storage};
+use csql::utils::Value;
+
+use csql::errors::{LoadingError, TableInitError};
+
+#[test]
+fn apply_select() -> Result<(), TableInitError> {
+    let buf = Buffer::new(Source::FilePath(String::from("samples/example1.csv")));
+    let headers = buf.read_headers().unwrap();
+    assert_eq!(headers, vec![String::from("a"), String::from("b"), String::from("c")]);
+    let mut t = Table::new("example1", &headers.iter().map(|h| h.as_str()).collect())?;
+    t.bulk_load_data(&buf.bulk_data().unwrap()).unwrap();
+    let expected: Vec<Vec<Value>> = vec![
+        vec![Value::Integer(1), Value::Integer(3)],
+        vec![Value::infer("x"), Value::infer("z")],
+    ];
+    let query_res = t.select(
+        &None,
+        &Columns::ColumnNames(vec![String::from("a"), String::from("c")]),
+        &None
+    ).unwrap();
+    assert_eq!(query_res, expected);
+    Ok(())
+}
+
+#[test]
+fn headerless_test() -> Result<(), LoadingError> {
+    let buf = Buffer::new(Source::FilePath(String::from("samples/example1.csv"))).with_header(false);
+    assert_eq!(buf.read_headers()?, vec![String::from("col1"), String::from("col2"), String::from("col3")]);
+    let data = buf.bulk_data()?;
+    assert_eq!(data.len(), 3);
+    assert_eq!(data[0], vec![Value::infer("a"), Value::infer("b"), Value::infer("c")]);
+    Ok(())
+}
+
+#[test]
+fn dump_data_test() -> Result<(), TableInitError> {
+    let path = std::env::temp_dir().join(format!("csql_dump_data_test_{}.csv", std::process::id()));
+    let path = path.to_str().unwrap();
+    fs::copy("samples/example1.csv", path).unwrap();
+    let mut buf = Buffer::load_from_source(path, SourceType::LocalFile).unwrap();
+    let mut t = Table::new("example1", &vec!["a", "b", "c"])?;
+    t.bulk_load_data(&buf.bulk_data().unwrap()).unwrap();
+    // A load-then-commit round trip leaves the file unchanged
+    buf.commit(&t).unwrap();
+    assert_eq!(fs::read_to_string(path).unwrap(), fs::read_to_string("samples/example1.csv").unwrap());
+    t.delete(&None, &Some(Condition::column_compared("a", ComparisonOperator::Equal, Value::infer("x")))).unwrap();
+    buf.commit(&t).unwrap();
+    assert_eq!(buf.read_headers().unwrap(), vec![String::from("a"), String::from("b"), String::from("c")]);
+    assert_eq!(buf.bulk_data().unwrap(), vec![vec![Value::Integer(1), Value::Integer(2), Value::Integer(3)]]);
+    fs::remove_file(path).unwrap();
+    Ok(())
+}
 
 #[test]
 fn directory_test() -> Result<(), LoadingError> {
@@ -51,4 +64,180 @@ fn directory_test() -> Result<(), LoadingError> {
     let config = directory.get_config().unwrap();
     println!("{:?}", config);
     Ok(())
-}
\ No newline at end of file
+}
+#[test]
+fn schema_loading_test() -> Result<(), LoadingError> {
+    let mut directory = Directory::new(String::from("./fake_data/"))?;
+    directory.load_buffers()?;
+    let tables = directory.load_tables()?;
+    let post = tables.iter().find(|t| t.name() == "post").unwrap();
+    let schema = post.schema().unwrap();
+    assert_eq!(schema.columns_names(), vec!["id", "author_id", "title", "content", "created"]);
+    assert_eq!(schema.columns[4].column_type, ColumnType::Date);
+    assert_eq!(schema.primary_key, vec![String::from("id")]);
+    Ok(())
+}
+
+#[test]
+fn schema_mismatch_test() -> Result<(), LoadingError> {
+    for dir in ["./samples/invalid_schema/", "./samples/mismatched_columns/"] {
+        let mut directory = Directory::new(String::from(dir))?;
+        directory.load_buffers()?;
+        let res = directory.load_tables();
+        assert!(matches!(res, Err(LoadingError::SchemaMismatch(_))));
+    }
+    Ok(())
+}
+
+#[test]
+fn rfc4180_reading_test() -> Result<(), LoadingError> {
+    let buf = Buffer::new(Source::FilePath(String::from("samples/tricky/crlf.csv")));
+    assert_eq!(buf.read_headers()?, vec![String::from("id"), String::from("name"), String::from("quote"), String::from("note")]);
+    assert_eq!(buf.bulk_data()?, vec![
+        vec![Value::Integer(1), Value::infer("Doe; John"), Value::infer("He said \"hi\""), Value::infer("first line\r\nsecond line")],
+        vec![Value::Integer(2), Value::Null, Value::Text(String::new()), Value::infer("plain")],
+        vec![Value::Integer(3), Value::Text(String::new()), Value::infer("x"), Value::infer("a;b")],
+    ]);
+    for file in ["samples/tricky/unterminated.csv", "samples/tricky/after_quote.csv"] {
+        let buf = Buffer::new(Source::FilePath(String::from(file)));
+        assert!(matches!(buf.bulk_data(), Err(LoadingError::InvalidRecord(_))));
+    }
+    Ok(())
+}
+
+#[test]
+fn rfc4180_round_trip_test() -> Result<(), TableInitError> {
+    let path = std::env::temp_dir().join(format!("csql_round_trip_test_{}.csv", std::process::id()));
+    let path = path.to_str().unwrap();
+    // CRLF endings without a final line break are kept as they are
+    fs::copy("samples/tricky/crlf.csv", path).unwrap();
+    let mut buf = Buffer::load_from_source(path, SourceType::LocalFile).unwrap();
+    let mut t = Table::new("crlf", &vec!["id", "name", "quote", "note"])?;
+    t.bulk_load_data(&buf.bulk_data().unwrap()).unwrap();
+    buf.commit(&t).unwrap();
+    assert_eq!(fs::read(path).unwrap(), fs::read("samples/tricky/crlf.csv").unwrap());
+    // Values needing quotes are read back unchanged
+    let mut buf = Buffer::new(Source::FilePath(path.to_string()));
+    let mut t = Table::new("tricky", &vec!["a;b", "c\"d"])?;
+    let data = vec![
+        vec![Value::infer("\"quoted\""), Value::infer("semi;colon")],
+        vec![Value::infer("multi\nline"), Value::infer("cr\r\nlf")],
+        vec![Value::Text(String::new()), Value::Null],
+        vec![Value::infer(" spaced "), Value::infer("trailing;")],
+    ];
+    t.bulk_load_data(&data).unwrap();
+    buf.commit(&t).unwrap();
+    assert_eq!(buf.read_headers().unwrap(), vec![String::from("a;b"), String::from("c\"d")]);
+    assert_eq!(buf.bulk_data().unwrap(), data);
+    assert!(fs::read_to_string(path).unwrap().ends_with('\n'));
+    fs::remove_file(path).unwrap();
+    Ok(())
+}
+
+#[test]
+fn directory_line_ending_test() -> Result<(), LoadingError> {
+    let dir = std::env::temp_dir().join(format!("csql_line_ending_test_{}", std::process::id()));
+    fs::create_dir_all(&dir).unwrap();
+    fs::copy("samples/tricky/crlf.csv", dir.join("crlf.csv")).unwrap();
+    fs::write(dir.join("lf.csv"), "id;name\n1;Ann").unwrap();
+    let mut directory = Directory::new(dir.to_str().unwrap().to_string())?;
+    directory.load_buffers()?;
+    for t in directory.load_tables()? {
+        directory.commit(t.name(), &t).unwrap();
+    }
+    assert_eq!(fs::read(dir.join("crlf.csv")).unwrap(), fs::read("samples/tricky/crlf.csv").unwrap());
+    assert_eq!(fs::read_to_string(dir.join("lf.csv")).unwrap(), "id;name\n1;Ann");
+    fs::remove_dir_all(&dir).unwrap();
+    Ok(())
+}
+
+#[test]
+fn decimal_round_trip_test() -> Result<(), TableInitError> {
+    let path = std::env::temp_dir().join(format!("csql_decimal_round_trip_test_{}.csv", std::process::id()));
+    let path = path.to_str().unwrap();
+    fs::copy("samples/prices/price.csv", path).unwrap();
+    let mut buf = Buffer::new(Source::FilePath(path.to_string()));
+    let mut t = Table::new("price", &vec!["id", "product", "price"])?;
+    t.bulk_load_data(&buf.bulk_data().unwrap()).unwrap();
+    assert_eq!(buf.bulk_data().unwrap()[0][2].to_string(), "10.00");
+    // Decimals keep the way they are written, new floats being written with a decimal point
+    t.insert(&None, InsertElement::PlainValues(vec![Value::Integer(5), Value::infer("Cup"), Value::Float(3.0)])).unwrap();
+    buf.commit(&t).unwrap();
+    assert_eq!(
+        fs::read_to_string(path).unwrap(),
+        fs::read_to_string("samples/prices/price.csv").unwrap() + "5;Cup;3.0\n",
+    );
+    let data = buf.bulk_data().unwrap();
+    assert!(matches!(data[4][2], Value::Float(f) if f == 3.0));
+    assert_eq!(data.iter().map(|r| r[2].to_string()).collect::<Vec<String>>(), vec!["10.00", "9.50", "2", "1.5", "3.0"]);
+    fs::remove_file(path).unwrap();
+    Ok(())
+}
+
+#[test]
+fn dialect_sniffing_test() -> Result<(), LoadingError> {
+    let products = Buffer::new(Source::FilePath(String::from("samples/dialects/products.csv"))).sniff()?;
+    assert_eq!(products.delimiter, '|');
+    assert!(products.has_header);
+    let colors = Buffer::new(Source::FilePath(String::from("samples/dialects/colors.tsv"))).sniff()?;
+    assert_eq!(colors.delimiter, '\t');
+    assert!(!colors.has_header);
+    let scores = Buffer::new(Source::FilePath(String::from("samples/dialects/scores.csv"))).sniff()?;
+    assert_eq!(scores.delimiter, ',');
+    assert_eq!(scores.quote, '"');
+    let users = Buffer::new(Source::FilePath(String::from("fake_data/user.csv"))).sniff()?;
+    assert_eq!(users, Dialect::default());
+    Ok(())
+}
+
+#[test]
+fn dialect_round_trip_test() -> Result<(), TableInitError> {
+    let path = std::env::temp_dir().join(format!("csql_dialect_round_trip_test_{}.csv", std::process::id()));
+    let path = path.to_str().unwrap();
+    fs::copy("samples/dialects/mottos.csv", path).unwrap();
+    let dialect = Dialect { quote: '\'', escape: Some('\\'), null_token: String::from("\\N"), ..Dialect::default() };
+    let mut buf = Buffer::new(Source::FilePath(path.to_string())).with_dialect(dialect);
+    let mut t = Table::new("mottos", &vec!["id", "motto"])?;
+    t.bulk_load_data(&buf.bulk_data().unwrap()).unwrap();
+    t.insert(&None, InsertElement::PlainValues(vec![Value::Integer(3), Value::infer("\\N")])).unwrap();
+    buf.commit(&t).unwrap();
+    assert_eq!(
+        fs::read_to_string(path).unwrap(),
+        fs::read_to_string("samples/dialects/mottos.csv").unwrap() + "3;'\\\\N'\n",
+    );
+    assert_eq!(buf.bulk_data().unwrap()[2], vec![Value::Integer(3), Value::infer("\\N")]);
+    fs::remove_file(path).unwrap();
+    Ok(())
+}
+
+#[test]
+fn date_formats_test() -> Result<(), TableInitError> {
+    let path = std::env::temp_dir().join(format!("csql_date_formats_test_{}.csv", std::process::id()));
+    let path = path.to_str().unwrap();
+    fs::copy("samples/dialects/events.csv", path).unwrap();
+    let dialect = Dialect {
+        date_format: String::from("%d/%m/%Y"),
+        timestamp_format: String::from("%d/%m/%Y %H:%M"),
+        ..Dialect::default()
+    };
+    let mut buf = Buffer::new(Source::FilePath(path.to_string())).with_dialect(dialect);
+    let mut t = Table::new("events", &vec!["id", "name", "day", "start"])?;
+    t.bulk_load_data(&buf.bulk_data().unwrap()).unwrap();
+    assert_eq!(buf.bulk_data().unwrap()[0][2], Value::infer("2023-02-14"));
+    assert_eq!(buf.bulk_data().unwrap()[0][3], Value::infer("2023-02-14 09:30:00"));
+    t.insert(&None, InsertElement::PlainValues(vec![
+        Value::Integer(4),
+        Value::infer("Retro"),
+        Value::infer("2023-03-01"),
+        Value::infer("2023-03-01 10:00:00"),
+    ])).unwrap();
+    buf.commit(&t).unwrap();
+    assert_eq!(
+        fs::read_to_string(path).unwrap(),
+        fs::read_to_string("samples/dialects/events.csv").unwrap() + "4;Retro;01/03/2023;01/03/2023 10:00\n",
+    );
+    fs::remove_file(path).unwrap();
+    let invalid = Dialect { date_format: String::from("%d/%m/%Y %H"), ..Dialect::default() };
+    assert!(invalid.validate().is_err());
+    Ok(())
+}
@@ -0,0 +1,106 @@
+use csql::functions::{arithmetic, call, lookup};
+use csql::traits::ArithmeticOperator;
+use csql::utils::{Interval, Value};
+
+fn text(s: &str) -> Value {
+    Value::from(s)
+}
+
+#[test]
+fn string_functions_test() {
+    assert_eq!(call("UPPER", &[text("Doe")]), Ok(text("DOE")));
+    assert_eq!(call("LOWER", &[text("Doe")]), Ok(text("doe")));
+    assert_eq!(call("TRIM", &[text("  John ")]), Ok(text("John")));
+    assert_eq!(call("TRIM", &[text("xxJohnx"), text("x")]), Ok(text("John")));
+    assert_eq!(call("LTRIM", &[text("  John ")]), Ok(text("John ")));
+    assert_eq!(call("RTRIM", &[text("  John ")]), Ok(text("  John")));
+    assert_eq!(call("LENGTH", &[text("Mickaël")]), Ok(Value::Integer(7)));
+    assert_eq!(call("LENGTH", &[Value::Integer(-12)]), Ok(Value::Integer(3)));
+    assert_eq!(call("SUBSTR", &[text("hello"), Value::Integer(2), Value::Integer(3)]), Ok(text("ell")));
+    assert_eq!(call("SUBSTR", &[text("hello"), Value::Integer(3)]), Ok(text("llo")));
+    assert_eq!(call("SUBSTR", &[text("hello"), Value::Integer(0), Value::Integer(2)]), Ok(text("h")));
+    assert_eq!(call("SUBSTR", &[text("hello"), Value::Integer(9)]), Ok(text("")));
+    assert!(call("SUBSTR", &[text("hello"), Value::Integer(1), Value::Integer(-1)]).is_err());
+    assert_eq!(call("REPLACE", &[text("a-b-c"), text("-"), text("+")]), Ok(text("a+b+c")));
+    assert_eq!(call("CONCAT", &[text("a"), Value::Null, Value::Integer(1)]), Ok(text("a1")));
+}
+
+#[test]
+fn numeric_functions_test() {
+    assert_eq!(call("ABS", &[Value::Integer(-3)]), Ok(Value::Integer(3)));
+    assert_eq!(call("ABS", &[Value::Float(-2.5)]), Ok(Value::Float(2.5)));
+    assert_eq!(call("ABS", &[text("-4")]), Ok(Value::Integer(4)));
+    assert!(call("ABS", &[text("four")]).is_err());
+    assert_eq!(call("ROUND", &[Value::Float(2.567), Value::Integer(2)]), Ok(Value::Float(2.57)));
+    assert_eq!(call("ROUND", &[Value::Float(2.5)]), Ok(Value::Float(3.0)));
+    assert_eq!(call("ROUND", &[Value::Integer(1250), Value::Integer(-2)]), Ok(Value::Integer(1300)));
+    assert_eq!(call("FLOOR", &[Value::Float(-2.5)]), Ok(Value::Float(-3.0)));
+    assert_eq!(call("CEIL", &[Value::Float(2.1)]), Ok(Value::Float(3.0)));
+    assert_eq!(call("CEIL", &[Value::Integer(2)]), Ok(Value::Integer(2)));
+    assert_eq!(call("MOD", &[Value::Integer(7), Value::Integer(3)]), Ok(Value::Integer(1)));
+    assert!(call("MOD", &[Value::Integer(7), Value::Integer(0)]).is_err());
+}
+
+#[test]
+fn null_handling_functions_test() {
+    assert_eq!(call("UPPER", &[Value::Null]), Ok(Value::Null));
+    assert_eq!(call("ROUND", &[Value::Float(2.5), Value::Null]), Ok(Value::Null));
+    assert_eq!(call("COALESCE", &[Value::Null, Value::Null, Value::Integer(3)]), Ok(Value::Integer(3)));
+    assert_eq!(call("COALESCE", &[Value::Null]), Ok(Value::Null));
+    assert_eq!(call("IFNULL", &[Value::Null, text("x")]), Ok(text("x")));
+    assert_eq!(call("IFNULL", &[text("y"), text("x")]), Ok(text("y")));
+    assert_eq!(call("NULLIF", &[Value::Integer(1), Value::Float(1.0)]), Ok(Value::Null));
+    assert_eq!(call("NULLIF", &[Value::Integer(1), Value::Integer(2)]), Ok(Value::Integer(1)));
+}
+
+#[test]
+fn function_lookup_test() {
+    assert_eq!(lookup("upper").map(|f| f.name), Some("UPPER"));
+    assert_eq!(lookup("Ceiling").map(|f| f.arity), Some((1, 1)));
+    assert!(lookup("soundex").is_none());
+    assert!(lookup("coalesce").is_some_and(|f| f.accepts(5) && !f.accepts(0)));
+    assert!(call("UPPER", &[text("a"), text("b")]).is_err());
+    assert!(call("SOUNDEX", &[text("a")]).is_err());
+}
+
+#[test]
+fn date_functions_test() {
+    let date = Value::infer("2023-05-17");
+    let ts = Value::infer("2023-05-17 14:35:12");
+    assert_eq!(call("DATE_TRUNC", &[text("month"), date.clone()]), Ok(Value::infer("2023-05-01")));
+    assert_eq!(call("DATE_TRUNC", &[text("quarter"), ts.clone()]), Ok(Value::infer("2023-04-01 00:00:00")));
+    assert_eq!(call("DATE_TRUNC", &[text("week"), date.clone()]), Ok(Value::infer("2023-05-15")));
+    assert_eq!(call("DATE_TRUNC", &[text("hour"), ts.clone()]), Ok(Value::infer("2023-05-17 14:00:00")));
+    assert_eq!(call("DATE_TRUNC", &[text("hour"), date.clone()]), Ok(Value::infer("2023-05-17 00:00:00")));
+    assert!(call("DATE_TRUNC", &[text("fortnight"), date.clone()]).is_err());
+    assert_eq!(call("DATE_PART", &[text("year"), date.clone()]), Ok(Value::Integer(2023)));
+    assert_eq!(call("DATE_PART", &[text("dow"), date.clone()]), Ok(Value::Integer(3)));
+    assert_eq!(call("DATE_PART", &[text("doy"), date.clone()]), Ok(Value::Integer(137)));
+    assert_eq!(call("DATE_PART", &[text("minute"), text("2023-05-17 14:35:12")]), Ok(Value::Integer(35)));
+    assert_eq!(call("DATE_ADD", &[date.clone(), text("1 month 15 days")]), Ok(Value::infer("2023-07-02")));
+    assert_eq!(call("DATE_ADD", &[date.clone(), text("2 hours")]), Ok(Value::infer("2023-05-17 02:00:00")));
+    assert_eq!(call("DATE_ADD", &[Value::infer("2023-01-31"), text("1 month")]), Ok(Value::infer("2023-02-28")));
+    assert_eq!(call("DATE_SUB", &[ts.clone(), text("1 day 35 minutes")]), Ok(Value::infer("2023-05-16 14:00:12")));
+    assert_eq!(call("STRFTIME", &[text("%d/%m/%Y %H:%M"), ts.clone()]), Ok(text("17/05/2023 14:35")));
+    assert_eq!(call("STRFTIME", &[text("%H:%M"), date.clone()]), Ok(text("00:00")));
+    assert!(call("STRFTIME", &[text("%Z"), ts.clone()]).is_err());
+    assert!(matches!(call("NOW", &[]), Ok(Value::Timestamp(_))));
+    assert!(matches!(call("CURRENT_DATE", &[]), Ok(Value::Date(_))));
+}
+
+#[test]
+fn date_arithmetic_test() {
+    let date = Value::infer("2023-05-17");
+    let week = Value::Interval(Interval::parse("1 week").unwrap());
+    assert_eq!(arithmetic(&date, &ArithmeticOperator::Add, &week), Ok(Value::infer("2023-05-24")));
+    assert_eq!(arithmetic(&week, &ArithmeticOperator::Add, &date), Ok(Value::infer("2023-05-24")));
+    assert_eq!(arithmetic(&date, &ArithmeticOperator::Subtract, &Value::Integer(17)), Ok(Value::infer("2023-04-30")));
+    assert_eq!(arithmetic(&date, &ArithmeticOperator::Subtract, &Value::infer("2023-01-01")), Ok(Value::Integer(136)));
+    assert_eq!(
+        arithmetic(&Value::infer("2023-05-17 12:00:00"), &ArithmeticOperator::Subtract, &Value::infer("2023-05-15 18:30:00")),
+        Ok(Value::Interval(Interval::parse("1 day 17 hours 30 minutes").unwrap())),
+    );
+    assert_eq!(arithmetic(&week, &ArithmeticOperator::Add, &week), Ok(Value::Interval(Interval::parse("14 days").unwrap())));
+    assert!(arithmetic(&date, &ArithmeticOperator::Multiply, &Value::Integer(2)).is_err());
+    assert!(arithmetic(&date, &ArithmeticOperator::Add, &date).is_err());
+}
@@ -5,4 +5,12 @@ mod entities;
 mod file_parsing;
 
 #[cfg(test)]
-mod commands;
\ No newline at end of file
+mod commands;
+
+#[cfg(test)]
+mod engine;
+
+#[cfg(test)]
+mod utils;
+#[cfg(test)]
+mod functions;
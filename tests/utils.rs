@@ -0,0 +1,88 @@
+use std::cmp::Ordering;
+
+use csql::utils::{Interval, Value};
+
+#[test]
+fn value_inference_test() {
+    assert_eq!(Value::infer(""), Value::Null);
+    assert!(matches!(Value::infer("42"), Value::Integer(42)));
+    assert!(matches!(Value::infer("-1.5"), Value::Float(_)));
+    assert!(matches!(Value::infer("true"), Value::Boolean(true)));
+    assert!(matches!(Value::infer("2023-02-14"), Value::Date(_)));
+    assert!(matches!(Value::infer("2023-02-14 08:30:00"), Value::Timestamp(_)));
+    // Values which would be written differently stay texts, decimal numbers keeping their text
+    assert!(matches!(Value::infer("007"), Value::Text(_)));
+    assert!(matches!(Value::infer("2023-2-14"), Value::Text(_)));
+    assert!(matches!(Value::infer("1.50"), Value::Decimal(f, _) if f == 1.5));
+    assert!(matches!(Value::infer("10.00"), Value::Decimal(f, _) if f == 10.0));
+    assert!(matches!(Value::infer("2.5e3"), Value::Decimal(f, _) if f == 2500.0));
+    for raw in ["007.5", ".5", "1.", "1e", "inf", "NaN", "1_000.0"] {
+        assert!(matches!(Value::infer(raw), Value::Text(_)), "{}", raw);
+    }
+    for raw in ["", "42", "-1.5", "1.50", "2E3", "0.10", "true", "2023-02-14", "2023-02-14 08:30:00", "007", "hello"] {
+        let v = Value::infer(raw);
+        if !v.is_null() {
+            assert_eq!(v.to_string(), raw);
+        }
+    }
+}
+
+#[test]
+fn value_comparison_test() {
+    assert_eq!(Value::Integer(9).compare(&Value::Integer(10)), Some(Ordering::Less));
+    assert_eq!(Value::Integer(2).compare(&Value::Float(1.5)), Some(Ordering::Greater));
+    assert_eq!(Value::infer("2023-01-01").compare(&Value::infer("2022-12-31 23:59:59")), Some(Ordering::Greater));
+    assert_eq!(Value::Text(String::from("10")).compare(&Value::Integer(9)), Some(Ordering::Greater));
+    assert_eq!(Value::Integer(9).compare(&Value::Text(String::from("10"))), Some(Ordering::Less));
+    assert_eq!(Value::Text(String::from("abc")).compare(&Value::Text(String::from("abd"))), Some(Ordering::Less));
+    assert_eq!(Value::Null.compare(&Value::Null), None);
+    assert_eq!(Value::Integer(1).compare(&Value::Null), None);
+    assert_eq!(Value::Boolean(true).compare(&Value::Integer(1)), None);
+    assert_eq!(Value::Text(String::from("abc")).compare(&Value::Integer(5)), None);
+    assert_eq!(Value::infer("2023-01-01").compare(&Value::Text(String::from("abc"))), None);
+    assert_eq!(Value::Float(-0.0).compare(&Value::Integer(0)), Some(Ordering::Equal));
+    assert_eq!(Value::Float(-0.0), Value::Float(0.0));
+    assert_eq!(Value::Float(f64::NAN).compare(&Value::Float(f64::INFINITY)), Some(Ordering::Greater));
+    // Decimals are numbers, compared to texts as they are written
+    assert_eq!(Value::infer("1.50").compare(&Value::Integer(2)), Some(Ordering::Less));
+    assert_eq!(Value::infer("2E3"), Value::Integer(2000));
+    assert_eq!(Value::infer("1.50").compare(&Value::Text(String::from("1.50"))), Some(Ordering::Equal));
+    assert_eq!(Value::infer("1.50").compare(&Value::Text(String::from("1.5"))), Some(Ordering::Greater));
+}
+
+#[test]
+fn value_ordering_test() {
+    let mut values = vec![
+        Value::Text(String::from("b")),
+        Value::Integer(10),
+        Value::Null,
+        Value::Float(9.5),
+        Value::infer("2020-01-01"),
+        Value::Boolean(false),
+    ];
+    values.sort();
+    assert_eq!(values, vec![
+        Value::Null,
+        Value::Boolean(false),
+        Value::Float(9.5),
+        Value::Integer(10),
+        Value::infer("2020-01-01"),
+        Value::Text(String::from("b")),
+    ]);
+    assert_eq!(Value::Integer(1), Value::Float(1.0));
+}
+
+#[test]
+fn interval_test() {
+    let i = Interval::parse("1 year 2 mons 3 days 4 hours").unwrap();
+    assert_eq!((i.months, i.days, i.seconds), (14, 3, 14400));
+    assert_eq!(i.to_string(), "1 year 2 months 3 days 4 hours");
+    assert_eq!(Interval::parse(&i.to_string()), Some(i));
+    assert_eq!(Interval::parse("-90 SECONDS").map(|i| i.to_string()), Some(String::from("-1 minute -30 seconds")));
+    assert_eq!(Interval::parse("2 weeks"), Interval::parse("14 days"));
+    assert_eq!(Interval::parse("1 month"), Interval::parse("30 days"));
+    assert!(Interval::parse("1 fortnight").is_none());
+    assert!(Interval::parse("3").is_none());
+    assert_eq!(Interval::default().to_string(), "0 seconds");
+    assert!(Value::Interval(Interval::parse("1 day").unwrap()) < Value::Interval(Interval::parse("25 hours").unwrap()));
+}